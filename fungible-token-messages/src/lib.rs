@@ -91,6 +91,8 @@ pub enum Action {
     TotalSupply,
     BalanceOf(ActorId),
     Allowance(AllowanceInput),
+    TransferCreator(ActorId),
+    Minters,
 }
 
 #[derive(Debug, Encode, Decode, TypeInfo)]
@@ -103,6 +105,8 @@ pub enum Event {
     TotalSupply(u128),
     Balance(u128),
     Allowance(AllowanceReply),
+    CreatorTransferred(ActorId),
+    Minters(Vec<ActorId>),
 }
 
 #[derive(Debug, Encode, Decode, TypeInfo)]
@@ -66,6 +66,23 @@ impl FungibleToken {
             }
         }
     }
+    /// Token creator account passes the creator role to `account`.
+    fn transfer_creator(&mut self, account: &ActorId) {
+        if self.creator != msg::source() {
+            panic!("FungibleToken: Only token creator can transfer the creator role.");
+        }
+        if account == &ZERO_ID {
+            panic!("FungibleToken: Creator role to zero address.");
+        }
+        self.creator = *account;
+        self.admins.remove(account);
+    }
+    /// Returns the creator followed by the admins, everyone who can mint and burn tokens.
+    fn minters(&self) -> Vec<ActorId> {
+        let mut minters = vec![self.creator];
+        minters.extend(self.admins.iter().copied());
+        minters
+    }
     fn increase_total_supply(&mut self, amount: u128) {
         self.total_supply = self.total_supply.saturating_add(amount);
     }
@@ -310,6 +327,13 @@ pub unsafe extern "C" fn handle() {
             };
             msg::reply(Event::Allowance(allowance_reply), GAS_AMOUNT, 0);
         }
+        Action::TransferCreator(account) => {
+            FUNGIBLE_TOKEN.transfer_creator(&account);
+            msg::reply(Event::CreatorTransferred(account), GAS_AMOUNT, 0);
+        }
+        Action::Minters => {
+            msg::reply(Event::Minters(FUNGIBLE_TOKEN.minters()), GAS_AMOUNT, 0);
+        }
     }
 }
 
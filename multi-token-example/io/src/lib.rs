@@ -0,0 +1,98 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub struct InitConfig {
+    pub name: String,
+    pub symbol: String,
+    pub base_uri: String,
+}
+
+#[derive(Encode, Debug, Decode, TypeInfo)]
+pub enum Action {
+    Mint {
+        to: ActorId,
+        id: U256,
+        amount: u128,
+        uri: Option<String>,
+    },
+    Burn {
+        from: ActorId,
+        id: U256,
+        amount: u128,
+    },
+    Transfer {
+        from: ActorId,
+        to: ActorId,
+        id: U256,
+        amount: u128,
+    },
+    TransferBatch {
+        from: ActorId,
+        to: ActorId,
+        ids: Vec<U256>,
+        amounts: Vec<u128>,
+    },
+    ApproveForAll {
+        operator: ActorId,
+        approved: bool,
+    },
+    BalanceOf {
+        account: ActorId,
+        id: U256,
+    },
+    BalanceOfBatch {
+        accounts: Vec<ActorId>,
+        ids: Vec<U256>,
+    },
+    Uri(U256),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    TransferSingle {
+        operator: ActorId,
+        from: ActorId,
+        to: ActorId,
+        id: U256,
+        amount: u128,
+    },
+    TransferBatch {
+        operator: ActorId,
+        from: ActorId,
+        to: ActorId,
+        ids: Vec<U256>,
+        amounts: Vec<u128>,
+    },
+    ApprovalForAll {
+        owner: ActorId,
+        operator: ActorId,
+        approved: bool,
+    },
+    BalanceOf(u128),
+    BalanceOfBatch(Vec<u128>),
+    Uri {
+        id: U256,
+        uri: String,
+    },
+}
+
+#[derive(Debug, Decode, Encode, TypeInfo)]
+pub enum State {
+    BalanceOf { account: ActorId, id: U256 },
+    TotalSupply(U256),
+    Uri(U256),
+    IsApprovedForAll { owner: ActorId, operator: ActorId },
+}
+
+#[derive(Debug, Decode, Encode, TypeInfo)]
+pub enum StateReply {
+    BalanceOf(u128),
+    TotalSupply(u128),
+    Uri(String),
+    IsApprovedForAll(bool),
+}
@@ -0,0 +1,128 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use gstd::{debug, msg, prelude::*, ActorId};
+use multi_token_example_io::{Action, Event, InitConfig, State, StateReply};
+use primitive_types::U256;
+
+use multi_token::base::MultiTokenBase;
+use multi_token::MultiToken;
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+
+#[derive(Debug)]
+pub struct GameItems {
+    pub token: MultiToken,
+    pub owner: ActorId,
+}
+
+static mut CONTRACT: GameItems = GameItems {
+    token: MultiToken::new(),
+    owner: ZERO_ID,
+};
+
+impl GameItems {
+    fn mint(&mut self, to: &ActorId, id: U256, amount: u128, uri: Option<String>) {
+        if msg::source() != self.owner {
+            panic!("MultiToken: Only the collection owner can mint tokens");
+        }
+        self.token.mint(to, id, amount, uri);
+    }
+}
+
+gstd::metadata! {
+    title: "Multi Token Example",
+        init:
+            input: InitConfig,
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn handle() {
+    let action: Action = msg::load().expect("Could not load Action");
+    match action {
+        Action::Mint {
+            to,
+            id,
+            amount,
+            uri,
+        } => {
+            CONTRACT.mint(&to, id, amount, uri);
+        }
+        Action::Burn { from, id, amount } => {
+            CONTRACT.token.burn(&msg::source(), &from, id, amount);
+        }
+        Action::Transfer {
+            from,
+            to,
+            id,
+            amount,
+        } => {
+            CONTRACT
+                .token
+                .transfer(&msg::source(), &from, &to, id, amount);
+        }
+        Action::TransferBatch {
+            from,
+            to,
+            ids,
+            amounts,
+        } => {
+            CONTRACT
+                .token
+                .transfer_batch(&msg::source(), &from, &to, ids, amounts);
+        }
+        Action::ApproveForAll { operator, approved } => {
+            CONTRACT
+                .token
+                .approve_for_all(&msg::source(), &operator, approved);
+        }
+        Action::BalanceOf { account, id } => {
+            CONTRACT.token.balance_of(&account, id);
+        }
+        Action::BalanceOfBatch { accounts, ids } => {
+            CONTRACT.token.balance_of_batch(accounts, ids);
+        }
+        Action::Uri(id) => {
+            CONTRACT.token.uri(id);
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn init() {
+    let config: InitConfig = msg::load().expect("Unable to decode InitConfig");
+    debug!("MultiToken {:?}", config);
+    CONTRACT
+        .token
+        .init(config.name, config.symbol, config.base_uri);
+    CONTRACT.owner = msg::source();
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::BalanceOf { account, id } => {
+            StateReply::BalanceOf(CONTRACT.token.get_balance(&account, id)).encode()
+        }
+        State::TotalSupply(id) => {
+            StateReply::TotalSupply(CONTRACT.token.get_total_supply(id)).encode()
+        }
+        State::Uri(id) => StateReply::Uri(CONTRACT.token.get_uri(id)).encode(),
+        State::IsApprovedForAll { owner, operator } => {
+            StateReply::IsApprovedForAll(CONTRACT.token.is_approved(&operator, &owner)).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
@@ -0,0 +1,289 @@
+use codec::Encode;
+use gtest::{Program, System};
+use multi_token_example_io::*;
+
+const USERS: &'static [u64] = &[3, 4, 5];
+
+fn init_with_mint<'a>(sys: &'a System) {
+    sys.init_logger();
+
+    let mt = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/multi_token_example.wasm",
+    );
+
+    let res = mt.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("GameItems"),
+            symbol: String::from("GIT"),
+            base_uri: String::from("https://"),
+        },
+    );
+
+    assert!(res.log().is_empty());
+
+    let res = mt.send(
+        USERS[0],
+        Action::Mint {
+            to: USERS[0].into(),
+            id: 0_i32.into(),
+            amount: 100,
+            uri: Some(String::from("https://sword")),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferSingle {
+            operator: USERS[0].into(),
+            from: 0.into(),
+            to: USERS[0].into(),
+            id: 0_i32.into(),
+            amount: 100,
+        }
+        .encode()
+    )));
+
+    let res = mt.send(
+        USERS[0],
+        Action::Mint {
+            to: USERS[0].into(),
+            id: 1_i32.into(),
+            amount: 10,
+            uri: None,
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn mint() {
+    let sys = System::new();
+    init_with_mint(&sys);
+}
+
+#[test]
+fn mint_failures() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let mt = sys.get_program(1);
+    // must fail since the caller isn't the collection owner
+    let res = mt.send(
+        USERS[1],
+        Action::Mint {
+            to: USERS[1].into(),
+            id: 0_i32.into(),
+            amount: 1,
+            uri: None,
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the total supply overflows
+    let res = mt.send(
+        USERS[0],
+        Action::Mint {
+            to: USERS[1].into(),
+            id: 0_i32.into(),
+            amount: u128::MAX,
+            uri: None,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn burn() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let mt = sys.get_program(1);
+    let res = mt.send(
+        USERS[0],
+        Action::Burn {
+            from: USERS[0].into(),
+            id: 0_i32.into(),
+            amount: 40,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferSingle {
+            operator: USERS[0].into(),
+            from: USERS[0].into(),
+            to: 0.into(),
+            id: 0_i32.into(),
+            amount: 40,
+        }
+        .encode()
+    )));
+
+    // must fail since the burn amount exceeds the balance
+    let res = mt.send(
+        USERS[0],
+        Action::Burn {
+            from: USERS[0].into(),
+            id: 0_i32.into(),
+            amount: 100,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn transfer_batch() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let mt = sys.get_program(1);
+    let res = mt.send(
+        USERS[0],
+        Action::TransferBatch {
+            from: USERS[0].into(),
+            to: USERS[1].into(),
+            ids: vec![0_i32.into(), 1_i32.into()],
+            amounts: vec![30, 5],
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferBatch {
+            operator: USERS[0].into(),
+            from: USERS[0].into(),
+            to: USERS[1].into(),
+            ids: vec![0_i32.into(), 1_i32.into()],
+            amounts: vec![30, 5],
+        }
+        .encode()
+    )));
+
+    let res = mt.send(
+        USERS[0],
+        Action::BalanceOfBatch {
+            accounts: vec![USERS[0].into(), USERS[1].into(), USERS[1].into()],
+            ids: vec![0_i32.into(), 0_i32.into(), 1_i32.into()],
+        },
+    );
+    assert!(res.contains(&(USERS[0], Event::BalanceOfBatch(vec![70, 30, 5]).encode())));
+}
+
+#[test]
+fn transfer_failures() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let mt = sys.get_program(1);
+    // must fail since the amount exceeds the balance
+    let res = mt.send(
+        USERS[0],
+        Action::Transfer {
+            from: USERS[0].into(),
+            to: USERS[1].into(),
+            id: 1_i32.into(),
+            amount: 11,
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the caller isn't an approved operator
+    let res = mt.send(
+        USERS[1],
+        Action::Transfer {
+            from: USERS[0].into(),
+            to: USERS[1].into(),
+            id: 0_i32.into(),
+            amount: 1,
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the lengths of `ids` and `amounts` differ
+    let res = mt.send(
+        USERS[0],
+        Action::TransferBatch {
+            from: USERS[0].into(),
+            to: USERS[1].into(),
+            ids: vec![0_i32.into(), 1_i32.into()],
+            amounts: vec![1],
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn approve_for_all() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let mt = sys.get_program(1);
+    let res = mt.send(
+        USERS[0],
+        Action::ApproveForAll {
+            operator: USERS[1].into(),
+            approved: true,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::ApprovalForAll {
+            owner: USERS[0].into(),
+            operator: USERS[1].into(),
+            approved: true,
+        }
+        .encode()
+    )));
+
+    let res = mt.send(
+        USERS[1],
+        Action::Transfer {
+            from: USERS[0].into(),
+            to: USERS[2].into(),
+            id: 0_i32.into(),
+            amount: 10,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = mt.send(
+        USERS[0],
+        Action::ApproveForAll {
+            operator: USERS[1].into(),
+            approved: false,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // must fail since the approval has been revoked
+    let res = mt.send(
+        USERS[1],
+        Action::Transfer {
+            from: USERS[0].into(),
+            to: USERS[2].into(),
+            id: 0_i32.into(),
+            amount: 10,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn uri() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let mt = sys.get_program(1);
+    let res = mt.send(USERS[0], Action::Uri(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Uri {
+            id: 0_i32.into(),
+            uri: String::from("https://sword"),
+        }
+        .encode()
+    )));
+
+    // falls back to the base URI
+    let res = mt.send(USERS[0], Action::Uri(1_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Uri {
+            id: 1_i32.into(),
+            uri: String::from("https://"),
+        }
+        .encode()
+    )));
+}
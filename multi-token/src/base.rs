@@ -0,0 +1,65 @@
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+
+pub trait MultiTokenBase {
+    /// Called during the multi-token contract deployment
+    /// Arguments:
+    /// * `name`: A descriptive name for a collection of tokens in this contract
+    /// * `symbol`: An abbreviated name for tokens in this contract
+    /// * `base_uri`: The URI returned for the token IDs that have no URI of their own
+    fn init(&mut self, name: String, symbol: String, base_uri: String);
+
+    /// Transfers `amount` of the token `id` from `from` to `to`
+    /// Arguments:
+    /// * `operator`: the valid ActorId. It can be `from` itself or the operator approved by `from`
+    /// * `from`: the account whose tokens are transferred
+    /// * `to`: the valid ActorId, the account to which the tokens will be sent
+    /// * `id`: the ID of the token to transfer
+    /// * `amount`: the amount of tokens to transfer
+    /// Contract must panic if `operator` is not allowed to manage the tokens of `from`,
+    /// if `to` is a zero ID or if `from` has insufficient balance
+    fn transfer(
+        &mut self,
+        operator: &ActorId,
+        from: &ActorId,
+        to: &ActorId,
+        id: U256,
+        amount: u128,
+    );
+
+    /// Transfers several tokens from `from` to `to` in a single call
+    /// Arguments:
+    /// * `ids`: the IDs of the tokens to transfer
+    /// * `amounts`: the amounts of tokens to transfer, `amounts[i]` is the amount of `ids[i]`
+    /// Contract must panic under the same conditions as `transfer` for any of the tokens
+    /// or if `ids` and `amounts` have different lengths
+    fn transfer_batch(
+        &mut self,
+        operator: &ActorId,
+        from: &ActorId,
+        to: &ActorId,
+        ids: Vec<U256>,
+        amounts: Vec<u128>,
+    );
+
+    /// Enables or disables the operator to manage all the tokens the owner has
+    /// Arguments:
+    /// * `owner`: the valid ActorId that must be the tokens owner
+    /// * `operator`: the valid ActorId that will be approved to manage the tokens
+    /// * `approved`: True if the operator is approved, false to revoke approval
+    /// Contract must panic if `operator` is a zero ID or `owner` itself
+    fn approve_for_all(&mut self, owner: &ActorId, operator: &ActorId, approved: bool);
+
+    /// Sends a message including the balance of the token `id` of `account`
+    fn balance_of(&self, account: &ActorId, id: U256);
+
+    /// Sends a message including the balances of several (account, token) pairs
+    /// Arguments:
+    /// * `accounts`: the accounts to query
+    /// * `ids`: the token IDs to query, `ids[i]` is queried for `accounts[i]`
+    /// Contract must panic if `accounts` and `ids` have different lengths
+    fn balance_of_batch(&self, accounts: Vec<ActorId>, ids: Vec<U256>);
+
+    /// Sends a message including the URI of the token `id`
+    fn uri(&self, id: U256);
+}
@@ -0,0 +1,302 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::{Decode, Encode};
+use gstd::{exec, msg, prelude::*, ActorId};
+pub mod base;
+use base::MultiTokenBase;
+
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+const GAS_RESERVE: u64 = 500_000_000;
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+
+#[derive(Debug)]
+pub struct MultiToken {
+    pub name: String,
+    pub symbol: String,
+    pub base_uri: String,
+    pub balances: BTreeMap<(ActorId, U256), u128>,
+    pub total_supply: BTreeMap<U256, u128>,
+    pub token_uris: BTreeMap<U256, String>,
+    pub operator_approvals: BTreeMap<ActorId, BTreeSet<ActorId>>,
+}
+
+impl MultiTokenBase for MultiToken {
+    fn init(&mut self, name: String, symbol: String, base_uri: String) {
+        self.name = name;
+        self.symbol = symbol;
+        self.base_uri = base_uri;
+    }
+
+    fn transfer(
+        &mut self,
+        operator: &ActorId,
+        from: &ActorId,
+        to: &ActorId,
+        id: U256,
+        amount: u128,
+    ) {
+        self.check_transfer(operator, from, to);
+        self.move_tokens(from, to, id, amount);
+
+        msg::reply(
+            Event::TransferSingle {
+                operator: *operator,
+                from: *from,
+                to: *to,
+                id,
+                amount,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    fn transfer_batch(
+        &mut self,
+        operator: &ActorId,
+        from: &ActorId,
+        to: &ActorId,
+        ids: Vec<U256>,
+        amounts: Vec<u128>,
+    ) {
+        if ids.len() != amounts.len() {
+            panic!("MultiToken: ids and amounts length mismatch");
+        }
+        self.check_transfer(operator, from, to);
+        for (id, amount) in ids.iter().zip(amounts.iter()) {
+            self.move_tokens(from, to, *id, *amount);
+        }
+
+        msg::reply(
+            Event::TransferBatch {
+                operator: *operator,
+                from: *from,
+                to: *to,
+                ids,
+                amounts,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    fn approve_for_all(&mut self, owner: &ActorId, operator: &ActorId, approved: bool) {
+        if operator == &ZERO_ID {
+            panic!("MultiToken: Approval for a zero address");
+        }
+        if operator == owner {
+            panic!("MultiToken: Approval to current owner");
+        }
+        let operators = self.operator_approvals.entry(*owner).or_default();
+        match approved {
+            true => operators.insert(*operator),
+            false => operators.remove(operator),
+        };
+
+        msg::reply(
+            Event::ApprovalForAll {
+                owner: *owner,
+                operator: *operator,
+                approved,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    fn balance_of(&self, account: &ActorId, id: U256) {
+        msg::reply(
+            Event::BalanceOf(self.get_balance(account, id)),
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    fn balance_of_batch(&self, accounts: Vec<ActorId>, ids: Vec<U256>) {
+        if accounts.len() != ids.len() {
+            panic!("MultiToken: accounts and ids length mismatch");
+        }
+        let balances = accounts
+            .iter()
+            .zip(ids.iter())
+            .map(|(account, id)| self.get_balance(account, *id))
+            .collect();
+        msg::reply(
+            Event::BalanceOfBatch(balances),
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    fn uri(&self, id: U256) {
+        msg::reply(
+            Event::Uri {
+                id,
+                uri: self.get_uri(id),
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+}
+
+impl MultiToken {
+    pub const fn new() -> MultiToken {
+        MultiToken {
+            name: String::new(),
+            symbol: String::new(),
+            base_uri: String::new(),
+            balances: BTreeMap::new(),
+            total_supply: BTreeMap::new(),
+            token_uris: BTreeMap::new(),
+            operator_approvals: BTreeMap::new(),
+        }
+    }
+
+    /// Creates `amount` of the token `id` and assigns them to `to`
+    /// Arguments:
+    /// * `to`: the valid ActorId that receives the tokens
+    /// * `id`: the ID of the token to mint
+    /// * `amount`: the amount of tokens to mint
+    /// * `uri`: the URI of the token `id`. It replaces the previous one if it is set
+    pub fn mint(&mut self, to: &ActorId, id: U256, amount: u128, uri: Option<String>) {
+        if to == &ZERO_ID {
+            panic!("MultiToken: Mint to zero address");
+        }
+        let supply = self
+            .get_total_supply(id)
+            .checked_add(amount)
+            .expect("MultiToken: Total supply overflow");
+        let balance = self
+            .get_balance(to, id)
+            .checked_add(amount)
+            .expect("MultiToken: Balance overflow");
+        self.balances.insert((*to, id), balance);
+        self.total_supply.insert(id, supply);
+        if let Some(uri) = uri {
+            self.token_uris.insert(id, uri);
+        }
+
+        msg::reply(
+            Event::TransferSingle {
+                operator: msg::source(),
+                from: ZERO_ID,
+                to: *to,
+                id,
+                amount,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    /// Destroys `amount` of the token `id` owned by `from`
+    /// Contract must panic if `operator` is not allowed to manage the tokens of `from`
+    /// or if `from` has insufficient balance
+    pub fn burn(&mut self, operator: &ActorId, from: &ActorId, id: U256, amount: u128) {
+        if !self.is_approved(operator, from) {
+            panic!("MultiToken: is not an authorized source");
+        }
+        let balance = self.get_balance(from, id);
+        if balance < amount {
+            panic!("MultiToken: burn amount exceeds balance");
+        }
+        self.balances.insert((*from, id), balance - amount);
+        let supply = self.get_total_supply(id);
+        self.total_supply.insert(id, supply.saturating_sub(amount));
+
+        msg::reply(
+            Event::TransferSingle {
+                operator: *operator,
+                from: *from,
+                to: ZERO_ID,
+                id,
+                amount,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    pub fn get_balance(&self, account: &ActorId, id: U256) -> u128 {
+        *self.balances.get(&(*account, id)).unwrap_or(&0)
+    }
+
+    pub fn get_total_supply(&self, id: U256) -> u128 {
+        *self.total_supply.get(&id).unwrap_or(&0)
+    }
+
+    pub fn get_uri(&self, id: U256) -> String {
+        self.token_uris
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| self.base_uri.clone())
+    }
+
+    /// Returns true if `operator` is `owner` itself or is approved to manage all tokens of `owner`
+    pub fn is_approved(&self, operator: &ActorId, owner: &ActorId) -> bool {
+        operator == owner
+            || self
+                .operator_approvals
+                .get(owner)
+                .map(|operators| operators.contains(operator))
+                .unwrap_or(false)
+    }
+
+    fn check_transfer(&self, operator: &ActorId, from: &ActorId, to: &ActorId) {
+        if to == &ZERO_ID {
+            panic!("MultiToken: Transfer to zero address");
+        }
+        if from == to {
+            panic!("MultiToken: Transfer to current owner");
+        }
+        if !self.is_approved(operator, from) {
+            panic!("MultiToken: is not an authorized source");
+        }
+    }
+
+    fn move_tokens(&mut self, from: &ActorId, to: &ActorId, id: U256, amount: u128) {
+        let from_balance = self.get_balance(from, id);
+        if from_balance < amount {
+            panic!("MultiToken: insufficient balance for transfer");
+        }
+        let to_balance = self
+            .get_balance(to, id)
+            .checked_add(amount)
+            .expect("MultiToken: Balance overflow");
+        self.balances.insert((*from, id), from_balance - amount);
+        self.balances.insert((*to, id), to_balance);
+    }
+}
+
+#[derive(Debug, Encode, TypeInfo, Decode)]
+pub enum Event {
+    TransferSingle {
+        operator: ActorId,
+        from: ActorId,
+        to: ActorId,
+        id: U256,
+        amount: u128,
+    },
+    TransferBatch {
+        operator: ActorId,
+        from: ActorId,
+        to: ActorId,
+        ids: Vec<U256>,
+        amounts: Vec<u128>,
+    },
+    ApprovalForAll {
+        owner: ActorId,
+        operator: ActorId,
+        approved: bool,
+    },
+    BalanceOf(u128),
+    BalanceOfBatch(Vec<u128>),
+    Uri {
+        id: U256,
+        uri: String,
+    },
+}
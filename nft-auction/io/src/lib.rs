@@ -0,0 +1,115 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub enum AuctionKind {
+    /// The price goes up with every bid, the highest bidder wins when the auction is over
+    English {
+        /// The minimum first bid
+        reserve_price: u128,
+        /// The minimum amount each next bid must exceed the highest one by
+        min_increment: u128,
+        /// A bid made this many milliseconds before the end extends the auction by this time
+        extension: u64,
+    },
+    /// The price goes down linearly from `start_price` to `end_price`, the first buyer wins
+    Dutch { start_price: u128, end_price: u128 },
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct Auction {
+    pub seller: ActorId,
+    pub kind: AuctionKind,
+    /// The block timestamp in milliseconds when the auction was created
+    pub start_time: u64,
+    /// The block timestamp in milliseconds when the auction is over
+    pub end_time: u64,
+    /// The highest bidder and the bid of the English auction
+    pub highest_bid: Option<(ActorId, u128)>,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Action {
+    Create {
+        nft_contract: ActorId,
+        token_id: U256,
+        kind: AuctionKind,
+        /// The auction duration in milliseconds
+        duration: u64,
+    },
+    Bid {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Buy {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Settle {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Cancel {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    AuctionCreated {
+        nft_contract: ActorId,
+        token_id: U256,
+        seller: ActorId,
+        kind: AuctionKind,
+        end_time: u64,
+    },
+    BidPlaced {
+        nft_contract: ActorId,
+        token_id: U256,
+        bidder: ActorId,
+        amount: u128,
+        end_time: u64,
+    },
+    /// `winner` is the zero address if the token was returned to the seller
+    AuctionSettled {
+        nft_contract: ActorId,
+        token_id: U256,
+        winner: ActorId,
+        price: u128,
+    },
+    AuctionCancelled {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    /// The NFT contract refused to transfer the token, the auction is kept
+    /// and the value paid for the token is refunded
+    TransferFailed {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum State {
+    Auction {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Auctions,
+    CurrentPrice {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Auction(Option<Auction>),
+    Auctions(Vec<(ActorId, U256, Auction)>),
+    CurrentPrice(Option<u128>),
+}
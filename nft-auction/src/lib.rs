@@ -0,0 +1,392 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use gstd::{debug, exec, msg, prelude::*, ActorId};
+use nft_auction_io::{Action, Auction, AuctionKind, Event, State, StateReply};
+use nft_example_client::{nft_owner, nft_transfer};
+use primitive_types::U256;
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+
+#[derive(Debug)]
+pub struct AuctionHouse {
+    /// Running auctions by the NFT contract and the token ID, the tokens are held by the auction house
+    pub auctions: BTreeMap<(ActorId, U256), Auction>,
+}
+
+static mut AUCTION_HOUSE: AuctionHouse = AuctionHouse {
+    auctions: BTreeMap::new(),
+};
+
+fn pay(to: &ActorId, amount: u128) {
+    if amount > 0 {
+        msg::send_bytes(*to, b"", 0, amount);
+    }
+}
+
+/// Returns the price of the Dutch auction at `now`
+/// The price goes down linearly from the start price to the end price during the auction
+pub fn dutch_price(auction: &Auction, now: u64) -> u128 {
+    let (start_price, end_price) = match auction.kind {
+        AuctionKind::Dutch {
+            start_price,
+            end_price,
+        } => (start_price, end_price),
+        AuctionKind::English { .. } => panic!("Auction: Auction is not Dutch"),
+    };
+    if now >= auction.end_time {
+        return end_price;
+    }
+    let duration = (auction.end_time - auction.start_time) as u128;
+    let elapsed = now.saturating_sub(auction.start_time) as u128;
+    let diff = start_price - end_price;
+    // the same as `diff * elapsed / duration` without overflowing
+    let discount = diff / duration * elapsed + diff % duration * elapsed / duration;
+    start_price - discount
+}
+
+impl AuctionHouse {
+    /// Takes the token from the seller, who must have approved the auction house, and starts the auction
+    async fn create(
+        &mut self,
+        nft_contract: ActorId,
+        token_id: U256,
+        kind: AuctionKind,
+        duration: u64,
+    ) {
+        let seller = msg::source();
+        if duration == 0 {
+            panic!("Auction: Duration must be greater than zero");
+        }
+        match kind {
+            AuctionKind::English { min_increment, .. } if min_increment == 0 => {
+                panic!("Auction: Minimum increment must be greater than zero")
+            }
+            AuctionKind::Dutch {
+                start_price,
+                end_price,
+            } if start_price <= end_price => {
+                panic!("Auction: Start price must be greater than the end price")
+            }
+            _ => {}
+        }
+        if self.auctions.contains_key(&(nft_contract, token_id)) {
+            panic!("Auction: Token is already on auction");
+        }
+        if nft_owner(&nft_contract, token_id).await != Some(seller) {
+            panic!("Auction: Only the token owner can put it on auction");
+        }
+        if !nft_transfer(&nft_contract, &exec::program_id(), token_id).await {
+            panic!("Auction: Error in transferring the token");
+        }
+
+        let start_time = exec::block_timestamp();
+        let end_time = start_time.saturating_add(duration);
+        self.auctions.insert(
+            (nft_contract, token_id),
+            Auction {
+                seller,
+                kind: kind.clone(),
+                start_time,
+                end_time,
+                highest_bid: None,
+            },
+        );
+        msg::reply(
+            Event::AuctionCreated {
+                nft_contract,
+                token_id,
+                seller,
+                kind,
+                end_time,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Places the attached value as a bid in the English auction and refunds the outbid bidder
+    fn bid(&mut self, nft_contract: ActorId, token_id: U256) {
+        let bidder = msg::source();
+        let amount = msg::value();
+        let now = exec::block_timestamp();
+        let auction = self
+            .auctions
+            .get_mut(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+        let (reserve_price, min_increment, extension) = match auction.kind {
+            AuctionKind::English {
+                reserve_price,
+                min_increment,
+                extension,
+            } => (reserve_price, min_increment, extension),
+            AuctionKind::Dutch { .. } => panic!("Auction: Auction is not English"),
+        };
+        if now >= auction.end_time {
+            panic!("Auction: Auction is over");
+        }
+        if bidder == auction.seller {
+            panic!("Auction: Seller can not bid");
+        }
+        let min_bid = match auction.highest_bid {
+            Some((_, highest)) => highest.saturating_add(min_increment),
+            None => reserve_price,
+        };
+        if amount < min_bid {
+            panic!("Auction: Bid is too low");
+        }
+
+        if let Some((outbid, highest)) = auction.highest_bid {
+            pay(&outbid, highest);
+        }
+        auction.highest_bid = Some((bidder, amount));
+        // a late bid gives the other bidders time to answer
+        if auction.end_time - now < extension {
+            auction.end_time = now.saturating_add(extension);
+        }
+        msg::reply(
+            Event::BidPlaced {
+                nft_contract,
+                token_id,
+                bidder,
+                amount,
+                end_time: auction.end_time,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Sells the token of the Dutch auction for the current price and refunds the excess value
+    async fn buy(&mut self, nft_contract: ActorId, token_id: U256) {
+        let buyer = msg::source();
+        let now = exec::block_timestamp();
+        let auction = self
+            .auctions
+            .get(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+        if now >= auction.end_time {
+            panic!("Auction: Auction is over");
+        }
+        if buyer == auction.seller {
+            panic!("Auction: Seller can not buy the own token");
+        }
+        let price = dutch_price(auction, now);
+        if msg::value() < price {
+            panic!("Auction: Attached value is less than the price");
+        }
+        // while the token is being transferred, the auction is out of the map and can't be bought again
+        let auction = self
+            .auctions
+            .remove(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+
+        if !nft_transfer(&nft_contract, &buyer, token_id).await {
+            pay(&buyer, msg::value());
+            return self.fail_transfer(nft_contract, token_id, auction);
+        }
+        pay(&auction.seller, price);
+        pay(&buyer, msg::value() - price);
+        msg::reply(
+            Event::AuctionSettled {
+                nft_contract,
+                token_id,
+                winner: buyer,
+                price,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Gives the token to the highest bidder and pays the seller once the auction is over
+    /// The token goes back to the seller if nobody has bought it
+    /// If the NFT contract refuses the transfer, the winner is refunded and the auction is kept without bids
+    async fn settle(&mut self, nft_contract: ActorId, token_id: U256) {
+        let auction = self
+            .auctions
+            .get(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+        if exec::block_timestamp() < auction.end_time {
+            panic!("Auction: Auction is not over yet");
+        }
+        let mut auction = self
+            .auctions
+            .remove(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+
+        let (winner, price) = match auction.highest_bid {
+            Some((winner, price)) => {
+                if !nft_transfer(&nft_contract, &winner, token_id).await {
+                    // the winner gets the bid back, so the seller can take the token back later
+                    pay(&winner, price);
+                    auction.highest_bid = None;
+                    return self.fail_transfer(nft_contract, token_id, auction);
+                }
+                pay(&auction.seller, price);
+                (winner, price)
+            }
+            None => {
+                if !nft_transfer(&nft_contract, &auction.seller, token_id).await {
+                    return self.fail_transfer(nft_contract, token_id, auction);
+                }
+                (ZERO_ID, 0)
+            }
+        };
+        msg::reply(
+            Event::AuctionSettled {
+                nft_contract,
+                token_id,
+                winner,
+                price,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Stops the auction that has no bids and returns the token to the seller
+    async fn cancel(&mut self, nft_contract: ActorId, token_id: U256) {
+        let auction = self
+            .auctions
+            .get(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+        if auction.seller != msg::source() {
+            panic!("Auction: Only the seller can cancel the auction");
+        }
+        if auction.highest_bid.is_some() {
+            panic!("Auction: Auction with bids can not be cancelled");
+        }
+        let auction = self
+            .auctions
+            .remove(&(nft_contract, token_id))
+            .expect("Auction: Token is not on auction");
+
+        if !nft_transfer(&nft_contract, &auction.seller, token_id).await {
+            return self.fail_transfer(nft_contract, token_id, auction);
+        }
+        msg::reply(
+            Event::AuctionCancelled {
+                nft_contract,
+                token_id,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Puts the auction back after the NFT contract refused to transfer its token
+    fn fail_transfer(&mut self, nft_contract: ActorId, token_id: U256, auction: Auction) {
+        debug!("Auction: Unable to transfer {:?}", token_id);
+        self.auctions.insert((nft_contract, token_id), auction);
+        msg::reply(
+            Event::TransferFailed {
+                nft_contract,
+                token_id,
+            },
+            0,
+            0,
+        );
+    }
+}
+
+gstd::metadata! {
+    title: "NFT Auction",
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: Action = msg::load().expect("Could not load Action");
+    let auction_house = unsafe { &mut AUCTION_HOUSE };
+    match action {
+        Action::Create {
+            nft_contract,
+            token_id,
+            kind,
+            duration,
+        } => {
+            auction_house
+                .create(nft_contract, token_id, kind, duration)
+                .await;
+        }
+        Action::Bid {
+            nft_contract,
+            token_id,
+        } => {
+            auction_house.bid(nft_contract, token_id);
+        }
+        Action::Buy {
+            nft_contract,
+            token_id,
+        } => {
+            auction_house.buy(nft_contract, token_id).await;
+        }
+        Action::Settle {
+            nft_contract,
+            token_id,
+        } => {
+            auction_house.settle(nft_contract, token_id).await;
+        }
+        Action::Cancel {
+            nft_contract,
+            token_id,
+        } => {
+            auction_house.cancel(nft_contract, token_id).await;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::Auction {
+            nft_contract,
+            token_id,
+        } => {
+            let auction = AUCTION_HOUSE
+                .auctions
+                .get(&(nft_contract, token_id))
+                .cloned();
+            StateReply::Auction(auction).encode()
+        }
+        State::Auctions => {
+            let auctions = AUCTION_HOUSE
+                .auctions
+                .iter()
+                .map(|((nft_contract, token_id), auction)| {
+                    (*nft_contract, *token_id, auction.clone())
+                })
+                .collect();
+            StateReply::Auctions(auctions).encode()
+        }
+        State::CurrentPrice {
+            nft_contract,
+            token_id,
+        } => {
+            let price = AUCTION_HOUSE
+                .auctions
+                .get(&(nft_contract, token_id))
+                .map(|auction| match auction.kind {
+                    AuctionKind::English { reserve_price, .. } => auction
+                        .highest_bid
+                        .map(|(_, highest)| highest)
+                        .unwrap_or(reserve_price),
+                    AuctionKind::Dutch { .. } => dutch_price(auction, exec::block_timestamp()),
+                });
+            StateReply::CurrentPrice(price).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
@@ -0,0 +1,429 @@
+use codec::{Decode, Encode};
+use gtest::{Program, System};
+use nft_auction_io::*;
+use nft_example_io::{
+    Action as NFTAction, Event as NFTEvent, ExpiryConfig, InitConfig as NFTInitConfig,
+};
+
+const USERS: &'static [u64] = &[4, 5, 6];
+const NFT_ID: u64 = 1;
+const AUCTION_ID: u64 = 2;
+// the block timestamp of gtest goes up by a second with every block
+const BLOCK_DURATION: u64 = 1_000;
+
+fn init(sys: &System) {
+    init_with_expiry(sys, None);
+}
+
+fn init_with_expiry(sys: &System, expiry: Option<ExpiryConfig>) {
+    sys.init_logger();
+
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        NFTInitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            expiry,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], NFTAction::Mint);
+    assert!(!res.main_failed());
+
+    let auction = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_auction.wasm",
+    );
+    let res = auction.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+}
+
+fn create(sys: &System, kind: AuctionKind, duration: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let auction = sys.get_program(AUCTION_ID);
+    let res = nft.send(
+        USERS[0],
+        NFTAction::Approve {
+            to: AUCTION_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = auction.send(
+        USERS[0],
+        Action::Create {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            kind,
+            duration,
+        },
+    );
+    assert!(!res.main_failed());
+    assert_owner(sys, AUCTION_ID);
+}
+
+fn assert_owner(sys: &System, owner: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(USERS[0], NFTAction::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], NFTEvent::OwnerOf(owner.into()).encode())));
+}
+
+fn bid() -> Action {
+    Action::Bid {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+    }
+}
+
+fn settle() -> Action {
+    Action::Settle {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+    }
+}
+
+#[test]
+fn create_failures() {
+    let sys = System::new();
+    init(&sys);
+    let auction = sys.get_program(AUCTION_ID);
+    let action = |kind, duration| Action::Create {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+        kind,
+        duration,
+    };
+    let english = || AuctionKind::English {
+        reserve_price: 100,
+        min_increment: 10,
+        extension: 0,
+    };
+    // must fail since the auction house isn't approved
+    let res = auction.send(USERS[0], action(english(), 10 * BLOCK_DURATION));
+    assert!(res.main_failed());
+    // must fail since the caller isn't the token owner
+    let res = auction.send(USERS[1], action(english(), 10 * BLOCK_DURATION));
+    assert!(res.main_failed());
+    // must fail since the duration is zero
+    let res = auction.send(USERS[0], action(english(), 0));
+    assert!(res.main_failed());
+    // must fail since the minimum increment is zero
+    let res = auction.send(
+        USERS[0],
+        action(
+            AuctionKind::English {
+                reserve_price: 100,
+                min_increment: 0,
+                extension: 0,
+            },
+            10 * BLOCK_DURATION,
+        ),
+    );
+    assert!(res.main_failed());
+    // must fail since the Dutch price doesn't go down
+    let res = auction.send(
+        USERS[0],
+        action(
+            AuctionKind::Dutch {
+                start_price: 100,
+                end_price: 100,
+            },
+            10 * BLOCK_DURATION,
+        ),
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn english_auction() {
+    let sys = System::new();
+    init(&sys);
+    create(
+        &sys,
+        AuctionKind::English {
+            reserve_price: 1_000,
+            min_increment: 100,
+            extension: 30 * BLOCK_DURATION,
+        },
+        100 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+
+    // must fail since the bid is below the reserve price
+    let res = auction.send_with_value(USERS[1], bid(), 999);
+    assert!(res.main_failed());
+    // must fail since the seller can't bid
+    let res = auction.send_with_value(USERS[0], bid(), 1_000);
+    assert!(res.main_failed());
+    let res = auction.send_with_value(USERS[1], bid(), 1_000);
+    assert!(!res.main_failed());
+    // must fail since the bid doesn't exceed the highest one by the minimum increment
+    let res = auction.send_with_value(USERS[2], bid(), 1_099);
+    assert!(res.main_failed());
+    // the first bidder gets the bid back
+    let res = auction.send_with_value(USERS[2], bid(), 1_100);
+    assert!(!res.main_failed());
+    assert!(res.contains(&(USERS[1], Vec::<u8>::new())));
+
+    // must fail since the auction with bids can't be cancelled
+    let res = auction.send(
+        USERS[0],
+        Action::Cancel {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // the late bid extends the auction by 30 seconds
+    sys.spend_blocks(80);
+    let res = auction.send_with_value(USERS[1], bid(), 1_200);
+    assert!(!res.main_failed());
+    sys.spend_blocks(15);
+    // must fail since the auction has been extended
+    let res = auction.send(USERS[0], settle());
+    assert!(res.main_failed());
+
+    sys.spend_blocks(20);
+    // must fail since the auction is over
+    let res = auction.send_with_value(USERS[2], bid(), 2_000);
+    assert!(res.main_failed());
+    let res = auction.send(USERS[2], settle());
+    assert!(res.contains(&(
+        USERS[2],
+        Event::AuctionSettled {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            winner: USERS[1].into(),
+            price: 1_200,
+        }
+        .encode()
+    )));
+    assert_owner(&sys, USERS[1]);
+}
+
+#[test]
+fn english_auction_without_bids() {
+    let sys = System::new();
+    init(&sys);
+    create(
+        &sys,
+        AuctionKind::English {
+            reserve_price: 1_000,
+            min_increment: 100,
+            extension: 0,
+        },
+        10 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+    sys.spend_blocks(20);
+    let res = auction.send(USERS[1], settle());
+    assert!(res.contains(&(
+        USERS[1],
+        Event::AuctionSettled {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            winner: 0.into(),
+            price: 0,
+        }
+        .encode()
+    )));
+    assert_owner(&sys, USERS[0]);
+    // must fail since the auction is settled
+    let res = auction.send(USERS[1], settle());
+    assert!(res.main_failed());
+}
+
+#[test]
+fn cancel() {
+    let sys = System::new();
+    init(&sys);
+    create(
+        &sys,
+        AuctionKind::English {
+            reserve_price: 1_000,
+            min_increment: 100,
+            extension: 0,
+        },
+        10 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+    let action = || Action::Cancel {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+    };
+    // must fail since the caller isn't the seller
+    let res = auction.send(USERS[1], action());
+    assert!(res.main_failed());
+    let res = auction.send(USERS[0], action());
+    assert!(res.contains(&(
+        USERS[0],
+        Event::AuctionCancelled {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    assert_owner(&sys, USERS[0]);
+}
+
+#[test]
+fn dutch_auction() {
+    let sys = System::new();
+    init(&sys);
+    // the price goes down by 10 every second
+    create(
+        &sys,
+        AuctionKind::Dutch {
+            start_price: 10_000,
+            end_price: 1_000,
+        },
+        900 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+    let action = || Action::Buy {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+    };
+    // must fail since bids are only for English auctions
+    let res = auction.send_with_value(USERS[1], bid(), 10_000);
+    assert!(res.main_failed());
+    // must fail since the price is still about 10_000
+    let res = auction.send_with_value(USERS[1], action(), 9_000);
+    assert!(res.main_failed());
+
+    // the price is about 5_500 in the middle of the auction
+    sys.spend_blocks(450);
+    let res = auction.send_with_value(USERS[1], action(), 5_000);
+    assert!(res.main_failed());
+    let res = auction.send_with_value(USERS[1], action(), 6_000);
+    let price = res
+        .log()
+        .iter()
+        .find_map(|log| match Event::decode(&mut log.payload()) {
+            Ok(Event::AuctionSettled { winner, price, .. }) if winner == USERS[1].into() => {
+                Some(price)
+            }
+            _ => None,
+        })
+        .expect("No settlement in the reply");
+    assert!(price > 5_000 && price < 6_000);
+    // the seller is paid the price and the buyer gets the overpaid value back
+    assert!(res.contains(&(USERS[0], Vec::<u8>::new())));
+    assert!(res.contains(&(USERS[1], Vec::<u8>::new())));
+    assert_owner(&sys, USERS[1]);
+
+    // must fail since the token is sold
+    let res = auction.send_with_value(USERS[2], action(), 10_000);
+    assert!(res.main_failed());
+}
+
+#[test]
+fn dutch_auction_expired() {
+    let sys = System::new();
+    init(&sys);
+    create(
+        &sys,
+        AuctionKind::Dutch {
+            start_price: 10_000,
+            end_price: 1_000,
+        },
+        10 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+    sys.spend_blocks(20);
+    // must fail since the auction is over
+    let res = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+        10_000,
+    );
+    assert!(res.main_failed());
+    let res = auction.send(USERS[1], settle());
+    assert!(!res.main_failed());
+    assert_owner(&sys, USERS[0]);
+}
+
+#[test]
+fn expired_token() {
+    let expiry = || {
+        Some(ExpiryConfig {
+            period: 30 * BLOCK_DURATION,
+            extension_price: None,
+            transferable_when_expired: false,
+        })
+    };
+    let transfer_failed = || {
+        Event::TransferFailed {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    };
+
+    let sys = System::new();
+    init_with_expiry(&sys, expiry());
+    create(
+        &sys,
+        AuctionKind::English {
+            reserve_price: 1_000,
+            min_increment: 100,
+            extension: 0,
+        },
+        50 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+    let res = auction.send_with_value(USERS[1], bid(), 1_000);
+    assert!(!res.main_failed());
+
+    // the NFT contract refuses to transfer the expired token,
+    // so the winner gets the bid back and the auction is kept without bids
+    sys.spend_blocks(60);
+    let res = auction.send(USERS[2], settle());
+    assert!(res.contains(&(USERS[2], transfer_failed())));
+    assert!(res.contains(&(USERS[1], Vec::<u8>::new())));
+    assert_owner(&sys, AUCTION_ID);
+    let res = auction.send(
+        USERS[0],
+        Action::Cancel {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.contains(&(USERS[0], transfer_failed())));
+    assert_owner(&sys, AUCTION_ID);
+
+    let sys = System::new();
+    init_with_expiry(&sys, expiry());
+    create(
+        &sys,
+        AuctionKind::Dutch {
+            start_price: 10_000,
+            end_price: 1_000,
+        },
+        50 * BLOCK_DURATION,
+    );
+    let auction = sys.get_program(AUCTION_ID);
+    sys.spend_blocks(40);
+    // the buyer gets the attached value back and the auction stays open
+    let res = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+        10_000,
+    );
+    assert!(res.contains(&(USERS[1], transfer_failed())));
+    assert!(res.contains(&(USERS[1], Vec::<u8>::new())));
+    assert_owner(&sys, AUCTION_ID);
+}
@@ -0,0 +1,82 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+/// The part of the owner's tokens a delegate is allowed to act for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, TypeInfo)]
+pub enum Scope {
+    /// All tokens of all collections
+    All,
+    /// All tokens of the NFT contract
+    Collection(ActorId),
+    /// The specific token of the NFT contract
+    Token { contract: ActorId, token_id: U256 },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Action {
+    Delegate {
+        delegate: ActorId,
+        scope: Scope,
+    },
+    Revoke {
+        delegate: ActorId,
+        scope: Scope,
+    },
+    /// Revokes all delegations of the caller
+    RevokeAll,
+    /// Checks that `delegate` can act for all tokens of `owner`
+    CheckDelegateForAll {
+        delegate: ActorId,
+        owner: ActorId,
+    },
+    /// Checks that `delegate` can act for the tokens of `owner` in the collection,
+    /// a delegation for all tokens counts too
+    CheckDelegateForCollection {
+        delegate: ActorId,
+        owner: ActorId,
+        contract: ActorId,
+    },
+    /// Checks that `delegate` can act for the token of `owner`,
+    /// a delegation for its collection or all tokens counts too
+    CheckDelegateForToken {
+        delegate: ActorId,
+        owner: ActorId,
+        contract: ActorId,
+        token_id: U256,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    Delegated {
+        owner: ActorId,
+        delegate: ActorId,
+        scope: Scope,
+    },
+    Revoked {
+        owner: ActorId,
+        delegate: ActorId,
+        scope: Scope,
+    },
+    AllRevoked(ActorId),
+    /// The reply to the delegation checks
+    IsDelegate(bool),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum State {
+    /// The delegates of the owner with their scopes
+    DelegationsOf(ActorId),
+    /// The owners that delegated to the account with their scopes
+    DelegatorsOf(ActorId),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    DelegationsOf(Vec<(ActorId, Scope)>),
+    DelegatorsOf(Vec<(ActorId, Scope)>),
+}
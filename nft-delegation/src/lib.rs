@@ -0,0 +1,173 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use gstd::{msg, prelude::*, ActorId};
+use nft_delegation_io::{Action, Event, Scope, State, StateReply};
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+
+/// The registry doesn't check token ownership, so a program that relies on a delegation
+/// must check that `owner` holds the token itself
+#[derive(Debug)]
+pub struct Registry {
+    /// The delegates of each owner with their scopes
+    pub delegations: BTreeMap<ActorId, BTreeSet<(ActorId, Scope)>>,
+}
+
+static mut REGISTRY: Registry = Registry {
+    delegations: BTreeMap::new(),
+};
+
+impl Registry {
+    fn delegate(&mut self, delegate: &ActorId, scope: Scope) {
+        let owner = msg::source();
+        if delegate == &ZERO_ID {
+            panic!("Delegation: Delegation to zero address");
+        }
+        if delegate == &owner {
+            panic!("Delegation: Delegation to the owner");
+        }
+        if !self
+            .delegations
+            .entry(owner)
+            .or_default()
+            .insert((*delegate, scope))
+        {
+            panic!("Delegation: Delegation already exists");
+        }
+        msg::reply(
+            Event::Delegated {
+                owner,
+                delegate: *delegate,
+                scope,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn revoke(&mut self, delegate: &ActorId, scope: Scope) {
+        let owner = msg::source();
+        let delegations = self
+            .delegations
+            .get_mut(&owner)
+            .expect("Delegation: Delegation does not exist");
+        if !delegations.remove(&(*delegate, scope)) {
+            panic!("Delegation: Delegation does not exist");
+        }
+        if delegations.is_empty() {
+            self.delegations.remove(&owner);
+        }
+        msg::reply(
+            Event::Revoked {
+                owner,
+                delegate: *delegate,
+                scope,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn revoke_all(&mut self) {
+        let owner = msg::source();
+        self.delegations.remove(&owner);
+        msg::reply(Event::AllRevoked(owner), 0, 0);
+    }
+
+    /// Returns true if `owner` delegated any of the scopes to `delegate`
+    fn is_delegate(&self, delegate: &ActorId, owner: &ActorId, scopes: &[Scope]) -> bool {
+        self.delegations.get(owner).map_or(false, |delegations| {
+            scopes
+                .iter()
+                .any(|scope| delegations.contains(&(*delegate, *scope)))
+        })
+    }
+}
+
+gstd::metadata! {
+    title: "NFT Delegation Registry",
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn handle() {
+    let action: Action = msg::load().expect("Could not load Action");
+    match action {
+        Action::Delegate { delegate, scope } => {
+            REGISTRY.delegate(&delegate, scope);
+        }
+        Action::Revoke { delegate, scope } => {
+            REGISTRY.revoke(&delegate, scope);
+        }
+        Action::RevokeAll => {
+            REGISTRY.revoke_all();
+        }
+        Action::CheckDelegateForAll { delegate, owner } => {
+            let is_delegate = REGISTRY.is_delegate(&delegate, &owner, &[Scope::All]);
+            msg::reply(Event::IsDelegate(is_delegate), 0, 0);
+        }
+        Action::CheckDelegateForCollection {
+            delegate,
+            owner,
+            contract,
+        } => {
+            let scopes = [Scope::All, Scope::Collection(contract)];
+            let is_delegate = REGISTRY.is_delegate(&delegate, &owner, &scopes);
+            msg::reply(Event::IsDelegate(is_delegate), 0, 0);
+        }
+        Action::CheckDelegateForToken {
+            delegate,
+            owner,
+            contract,
+            token_id,
+        } => {
+            let scopes = [
+                Scope::All,
+                Scope::Collection(contract),
+                Scope::Token { contract, token_id },
+            ];
+            let is_delegate = REGISTRY.is_delegate(&delegate, &owner, &scopes);
+            msg::reply(Event::IsDelegate(is_delegate), 0, 0);
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::DelegationsOf(owner) => {
+            let delegations = REGISTRY
+                .delegations
+                .get(&owner)
+                .map(|delegations| delegations.iter().copied().collect())
+                .unwrap_or_default();
+            StateReply::DelegationsOf(delegations).encode()
+        }
+        State::DelegatorsOf(account) => {
+            let delegators = REGISTRY
+                .delegations
+                .iter()
+                .flat_map(|(owner, delegations)| {
+                    delegations
+                        .iter()
+                        .filter(|(delegate, _)| *delegate == account)
+                        .map(|(_, scope)| (*owner, *scope))
+                })
+                .collect();
+            StateReply::DelegatorsOf(delegators).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
@@ -0,0 +1,210 @@
+use codec::Encode;
+use gtest::{Program, System};
+use nft_delegation_io::*;
+
+const USERS: &'static [u64] = &[4, 5, 6];
+const NFT_ID: u64 = 10;
+const OTHER_NFT_ID: u64 = 11;
+
+fn init(sys: &System) -> Program {
+    sys.init_logger();
+    let registry = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_delegation.wasm",
+    );
+    let res = registry.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+    registry
+}
+
+fn delegate(registry: &Program, owner: u64, delegate: u64, scope: Scope) {
+    let res = registry.send(
+        owner,
+        Action::Delegate {
+            delegate: delegate.into(),
+            scope,
+        },
+    );
+    assert!(res.contains(&(
+        owner,
+        Event::Delegated {
+            owner: owner.into(),
+            delegate: delegate.into(),
+            scope,
+        }
+        .encode()
+    )));
+}
+
+fn check_token(
+    registry: &Program,
+    delegate: u64,
+    owner: u64,
+    contract: u64,
+    token_id: u64,
+) -> bool {
+    let res = registry.send(
+        USERS[2],
+        Action::CheckDelegateForToken {
+            delegate: delegate.into(),
+            owner: owner.into(),
+            contract: contract.into(),
+            token_id: token_id.into(),
+        },
+    );
+    res.contains(&(USERS[2], Event::IsDelegate(true).encode()))
+}
+
+#[test]
+fn delegate_token() {
+    let sys = System::new();
+    let registry = init(&sys);
+    delegate(
+        &registry,
+        USERS[0],
+        USERS[1],
+        Scope::Token {
+            contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(check_token(&registry, USERS[1], USERS[0], NFT_ID, 0));
+    assert!(!check_token(&registry, USERS[1], USERS[0], NFT_ID, 1));
+    assert!(!check_token(&registry, USERS[0], USERS[1], NFT_ID, 0));
+
+    // a token delegation doesn't cover its collection
+    let res = registry.send(
+        USERS[2],
+        Action::CheckDelegateForCollection {
+            delegate: USERS[1].into(),
+            owner: USERS[0].into(),
+            contract: NFT_ID.into(),
+        },
+    );
+    assert!(res.contains(&(USERS[2], Event::IsDelegate(false).encode())));
+}
+
+#[test]
+fn delegate_collection_and_all() {
+    let sys = System::new();
+    let registry = init(&sys);
+    delegate(
+        &registry,
+        USERS[0],
+        USERS[1],
+        Scope::Collection(NFT_ID.into()),
+    );
+    assert!(check_token(&registry, USERS[1], USERS[0], NFT_ID, 0));
+    assert!(check_token(&registry, USERS[1], USERS[0], NFT_ID, 1));
+    assert!(!check_token(&registry, USERS[1], USERS[0], OTHER_NFT_ID, 0));
+    let res = registry.send(
+        USERS[2],
+        Action::CheckDelegateForAll {
+            delegate: USERS[1].into(),
+            owner: USERS[0].into(),
+        },
+    );
+    assert!(res.contains(&(USERS[2], Event::IsDelegate(false).encode())));
+
+    delegate(&registry, USERS[0], USERS[2], Scope::All);
+    assert!(check_token(&registry, USERS[2], USERS[0], OTHER_NFT_ID, 0));
+    let res = registry.send(
+        USERS[2],
+        Action::CheckDelegateForCollection {
+            delegate: USERS[2].into(),
+            owner: USERS[0].into(),
+            contract: OTHER_NFT_ID.into(),
+        },
+    );
+    assert!(res.contains(&(USERS[2], Event::IsDelegate(true).encode())));
+}
+
+#[test]
+fn revoke() {
+    let sys = System::new();
+    let registry = init(&sys);
+    delegate(
+        &registry,
+        USERS[0],
+        USERS[1],
+        Scope::Collection(NFT_ID.into()),
+    );
+    delegate(&registry, USERS[0], USERS[2], Scope::All);
+
+    // must fail since the delegation was made by another owner
+    let res = registry.send(
+        USERS[1],
+        Action::Revoke {
+            delegate: USERS[1].into(),
+            scope: Scope::Collection(NFT_ID.into()),
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the scope differs from the delegated one
+    let res = registry.send(
+        USERS[0],
+        Action::Revoke {
+            delegate: USERS[1].into(),
+            scope: Scope::All,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = registry.send(
+        USERS[0],
+        Action::Revoke {
+            delegate: USERS[1].into(),
+            scope: Scope::Collection(NFT_ID.into()),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Revoked {
+            owner: USERS[0].into(),
+            delegate: USERS[1].into(),
+            scope: Scope::Collection(NFT_ID.into()),
+        }
+        .encode()
+    )));
+    assert!(!check_token(&registry, USERS[1], USERS[0], NFT_ID, 0));
+    assert!(check_token(&registry, USERS[2], USERS[0], NFT_ID, 0));
+
+    let res = registry.send(USERS[0], Action::RevokeAll);
+    assert!(res.contains(&(USERS[0], Event::AllRevoked(USERS[0].into()).encode())));
+    assert!(!check_token(&registry, USERS[2], USERS[0], NFT_ID, 0));
+}
+
+#[test]
+fn delegate_failures() {
+    let sys = System::new();
+    let registry = init(&sys);
+    // must fail since the delegate is the owner
+    let res = registry.send(
+        USERS[0],
+        Action::Delegate {
+            delegate: USERS[0].into(),
+            scope: Scope::All,
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the delegate is the zero address
+    let res = registry.send(
+        USERS[0],
+        Action::Delegate {
+            delegate: 0.into(),
+            scope: Scope::All,
+        },
+    );
+    assert!(res.main_failed());
+
+    delegate(&registry, USERS[0], USERS[1], Scope::All);
+    // must fail since the delegation already exists
+    let res = registry.send(
+        USERS[0],
+        Action::Delegate {
+            delegate: USERS[1].into(),
+            scope: Scope::All,
+        },
+    );
+    assert!(res.main_failed());
+}
@@ -0,0 +1,57 @@
+#![no_std]
+
+use gstd::{msg, prelude::*, ActorId};
+use nft_example_io::{Action, Event};
+use primitive_types::U256;
+
+/// The gas for the messages to the NFT contract
+/// The contract keeps a gas reserve when it replies, so it needs more gas than its handlers spend
+pub const GAS_FOR_NFT: u64 = 1_000_000_000;
+
+/// Returns the owner of the token, or None if the NFT contract couldn't be asked for it
+pub async fn nft_owner(nft_contract: &ActorId, token_id: U256) -> Option<ActorId> {
+    let reply = msg::send_and_wait_for_reply::<Event, _>(
+        *nft_contract,
+        Action::OwnerOf(token_id),
+        GAS_FOR_NFT,
+        0,
+    )
+    .await;
+    match reply {
+        Ok(Event::OwnerOf(owner)) => Some(owner),
+        _ => None,
+    }
+}
+
+/// Transfers the token to `to`, the calling program must be allowed to transfer it
+/// Returns false if the NFT contract refused the transfer
+pub async fn nft_transfer(nft_contract: &ActorId, to: &ActorId, token_id: U256) -> bool {
+    msg::send_and_wait_for_reply::<Event, _>(
+        *nft_contract,
+        Action::Transfer { to: *to, token_id },
+        GAS_FOR_NFT,
+        0,
+    )
+    .await
+    .is_ok()
+}
+
+/// Returns the royalty recipients with their amounts for the sale of the token for `price`,
+/// or None if the NFT contract failed to report the royalty
+pub async fn nft_royalty(
+    nft_contract: &ActorId,
+    token_id: U256,
+    price: u128,
+) -> Option<Vec<(ActorId, u128)>> {
+    let reply = msg::send_and_wait_for_reply::<Event, _>(
+        *nft_contract,
+        Action::Royalty { token_id, price },
+        GAS_FOR_NFT,
+        0,
+    )
+    .await;
+    match reply {
+        Ok(Event::Royalty { recipients, .. }) => Some(recipients),
+        _ => None,
+    }
+}
@@ -4,19 +4,201 @@ use codec::{Decode, Encode};
 use gstd::{prelude::*, ActorId};
 use primitive_types::U256;
 use scale_info::TypeInfo;
-use nft_example_io::{Action, Event};
+
+pub use non_fungible_token::provenance::OwnershipRecord;
+pub use non_fungible_token::token::{AttributeValue, TokenMetadata};
+
+#[derive(Debug, Default, Encode, Decode, TypeInfo)]
+pub struct InitConfig {
+    pub name: String,
+    pub symbol: String,
+    pub base_uri: String,
+    pub max_royalty_rate: u16,
+    pub mint_price: Option<u128>,
+    pub unrevealed_uri: Option<String>,
+    /// The hash of the ordered metadata, it's committed before anything is minted and checked on reveal,
+    /// so it's required together with `unrevealed_uri`
+    pub provenance_hash: Option<[u8; 32]>,
+    pub soulbound: bool,
+    pub soulbound_burnable: bool,
+    pub on_chain_uri: bool,
+    /// Makes the tokens valid only for a limited time after they are minted
+    pub expiry: Option<ExpiryConfig>,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct SaleRecord {
+    pub seller: ActorId,
+    pub buyer: ActorId,
+    pub price: u128,
+    pub block_height: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct RoyaltyChange {
+    pub rate: u16,
+    pub changed_by: ActorId,
+    pub block_height: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct PresaleConfig {
+    /// The root of the allowlist Merkle tree
+    /// Its leaves are `blake2_256(account ++ max_allowed)` of SCALE-encoded values,
+    /// the pairs of nodes are sorted before hashing
+    pub merkle_root: [u8; 32],
+    pub start_block: u32,
+    pub end_block: u32,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct ExpiryConfig {
+    /// The time in milliseconds a token stays valid after it's minted or extended by a period
+    pub period: u64,
+    /// The price of extending a token by one period, None to extend for free
+    pub extension_price: Option<u128>,
+    /// False to forbid transferring the tokens that have expired
+    pub transferable_when_expired: bool,
+}
 
 #[derive(Encode, Debug, Decode, TypeInfo)]
 pub enum Action {
     Mint,
     Burn(U256),
-    Transfer { to: ActorId, token_id: U256 },
-    Approve { to: ActorId, token_id: U256 },
-    ApproveForAll { to: ActorId, approved: bool },
+    Transfer {
+        to: ActorId,
+        token_id: U256,
+    },
+    /// `to_program` must be true if `to` is a program that has to accept the token
+    SafeTransfer {
+        to: ActorId,
+        token_id: U256,
+        data: Vec<u8>,
+        to_program: bool,
+    },
+    Approve {
+        to: ActorId,
+        token_id: U256,
+    },
+    ApproveForAll {
+        to: ActorId,
+        approved: bool,
+    },
     OwnerOf(U256),
     BalanceOf(ActorId),
-    Royalty { token_id: U256, price: u64 },
-    AssignRoyalty {token_id: U256, rate: u64},
+    Royalty {
+        token_id: U256,
+        price: u128,
+    },
+    AssignRoyalty {
+        token_id: U256,
+        rate: u16,
+        shares: Vec<(ActorId, u16)>,
+    },
+    Sale {
+        token_id: U256,
+        to: ActorId,
+    },
+    PauseMint,
+    UnpauseMint,
+    SetBaseUri(String),
+    SetMaxSupply(Option<U256>),
+    SetMintLimit(Option<u32>),
+    TransferOwnership(ActorId),
+    AcceptOwnership,
+    Withdraw,
+    SetPresale(PresaleConfig),
+    PresaleMint {
+        proof: Vec<[u8; 32]>,
+        max_allowed: u32,
+    },
+    StartPublicSale,
+    TokenUri(U256),
+    /// Fails unless `provenance_hash` is the one committed in `InitConfig`
+    Reveal {
+        base_uri: String,
+        provenance_hash: [u8; 32],
+    },
+    SetTokenSoulbound {
+        token_id: U256,
+        soulbound: bool,
+    },
+    Revoke(U256),
+    SetUser {
+        token_id: U256,
+        user: ActorId,
+        expires: u64,
+    },
+    UserOf(U256),
+    MintBatch {
+        count: u32,
+        to: ActorId,
+    },
+    TransferBatch {
+        to: ActorId,
+        token_ids: Vec<U256>,
+    },
+    SetMetadataUpdater {
+        updater: ActorId,
+        allowed: bool,
+    },
+    SetMetadata {
+        token_id: U256,
+        metadata: TokenMetadata,
+    },
+    SetAttribute {
+        token_id: U256,
+        key: String,
+        value: AttributeValue,
+    },
+    RemoveAttribute {
+        token_id: U256,
+        key: String,
+    },
+    FreezeMetadata(U256),
+    FreezeAllMetadata,
+    Nest {
+        token_id: U256,
+        parent: (ActorId, U256),
+    },
+    Detach {
+        token_id: U256,
+        to: ActorId,
+    },
+    RootOwner(U256),
+    Lock {
+        token_id: U256,
+        locker: ActorId,
+    },
+    Unlock(U256),
+    /// Extends the validity of the token by `periods` periods starting from its expiry,
+    /// or from now if it has already expired
+    Extend {
+        token_id: U256,
+        periods: u32,
+    },
+    /// Sets the royalty of the tokens that have no royalty of their own
+    SetDefaultRoyalty {
+        receiver: ActorId,
+        rate: u16,
+    },
+    DeleteDefaultRoyalty,
+    /// Removes the royalty of the token, so the default royalty applies to it
+    ResetTokenRoyalty(U256),
+    /// Returns the royalty changes of the token, including the burnt one
+    RoyaltyHistory(U256),
+    /// Asks for the root owner on behalf of the program the token's tree is nested into,
+    /// `hops` is the number of programs the query has passed through
+    ResolveRootOwner {
+        token_id: U256,
+        hops: u32,
+    },
+    /// Returns the ownership changes of the token from the oldest to the latest
+    TokenHistory {
+        token_id: U256,
+        offset: u32,
+        limit: u32,
+    },
 }
 
 #[derive(Debug, Encode, Decode, TypeInfo)]
@@ -38,12 +220,183 @@ pub enum Event {
     },
     OwnerOf(ActorId),
     BalanceOf(U256),
+    TransferRejected {
+        from: ActorId,
+        to: ActorId,
+        token_id: U256,
+    },
     Royalty {
-        amount: u64,
-        origin: ActorId,
+        amount: u128,
+        recipients: Vec<(ActorId, u128)>,
     },
     AssignRoyalty {
         token_id: U256,
-        recipient: ActorId,
+        shares: Vec<(ActorId, u16)>,
     },
+    Sale {
+        token_id: U256,
+        seller: ActorId,
+        buyer: ActorId,
+        price: u128,
+        royalties: Vec<(ActorId, u128)>,
+    },
+    MintPaused,
+    MintUnpaused,
+    BaseUriUpdated(String),
+    MaxSupplyUpdated(Option<U256>),
+    MintLimitUpdated(Option<u32>),
+    OwnershipTransferStarted {
+        owner: ActorId,
+        pending_owner: ActorId,
+    },
+    OwnershipTransferred {
+        previous_owner: ActorId,
+        new_owner: ActorId,
+    },
+    Withdrawn {
+        to: ActorId,
+        amount: u128,
+    },
+    PresaleSet(PresaleConfig),
+    PublicSaleStarted,
+    TokenUri(String),
+    Revealed {
+        base_uri: String,
+        provenance_hash: [u8; 32],
+    },
+    SoulboundUpdated {
+        token_id: U256,
+        soulbound: bool,
+    },
+    UpdateUser {
+        token_id: U256,
+        user: ActorId,
+        expires: u64,
+    },
+    UserOf(ActorId),
+    TransferBatch {
+        from: ActorId,
+        to: ActorId,
+        token_ids: Vec<U256>,
+    },
+    MetadataUpdaterSet {
+        updater: ActorId,
+        allowed: bool,
+    },
+    MetadataUpdate(U256),
+    MetadataFrozen(U256),
+    AllMetadataFrozen,
+    Nested {
+        token_id: U256,
+        parent: (ActorId, U256),
+    },
+    Detached {
+        token_id: U256,
+        to: ActorId,
+    },
+    RootOwner(ActorId),
+    Locked {
+        token_id: U256,
+        locker: ActorId,
+    },
+    Unlocked(U256),
+    Extended {
+        token_id: U256,
+        expires: u64,
+    },
+    DefaultRoyaltySet {
+        receiver: ActorId,
+        rate: u16,
+    },
+    DefaultRoyaltyDeleted,
+    TokenRoyaltyReset(U256),
+    RoyaltyHistory(Vec<RoyaltyChange>),
+    TokenHistory(Vec<OwnershipRecord>),
+}
+
+#[derive(Debug, Decode, Encode, TypeInfo)]
+pub enum State {
+    BalanceOfUser(ActorId),
+    TokenOwner(U256),
+    IsTokenOwner {
+        account: ActorId,
+        token_id: U256,
+    },
+    GetApproved(U256),
+    Sales(U256),
+    RoyaltyHistory(U256),
+    Owner,
+    PendingOwner,
+    IsMintPaused,
+    BaseUri,
+    MaxSupply,
+    MintLimit,
+    TotalMinted,
+    MintedBy(ActorId),
+    MintPrice,
+    Proceeds,
+    Presale,
+    IsPublicSale,
+    PresaleMintedBy(ActorId),
+    TokenUri(U256),
+    IsRevealed,
+    ProvenanceHash,
+    IsSoulbound(U256),
+    IsCollectionSoulbound,
+    UserOf(U256),
+    UserExpires(U256),
+    OwnersOf(Vec<U256>),
+    TokenMetadata(U256),
+    IsMetadataFrozen(U256),
+    ParentOf(U256),
+    ChildrenOf(U256),
+    RootOwner(U256),
+    LockerOf(U256),
+    TokenHistory {
+        token_id: U256,
+        offset: u32,
+        limit: u32,
+    },
+    IsValid(U256),
+    ExpiresAt(U256),
+}
+
+#[derive(Debug, Decode, Encode, TypeInfo)]
+pub enum StateReply {
+    BalanceOfUser(U256),
+    TokenOwner(ActorId),
+    IsTokenOwner(bool),
+    GetApproved(ActorId),
+    Sales(Vec<SaleRecord>),
+    RoyaltyHistory(Vec<RoyaltyChange>),
+    Owner(ActorId),
+    PendingOwner(Option<ActorId>),
+    IsMintPaused(bool),
+    BaseUri(String),
+    MaxSupply(Option<U256>),
+    MintLimit(Option<u32>),
+    TotalMinted(U256),
+    MintedBy(u32),
+    MintPrice(Option<u128>),
+    Proceeds(u128),
+    Presale(Option<PresaleConfig>),
+    IsPublicSale(bool),
+    PresaleMintedBy(u32),
+    TokenUri(String),
+    IsRevealed(bool),
+    ProvenanceHash(Option<[u8; 32]>),
+    IsSoulbound(bool),
+    IsCollectionSoulbound(bool),
+    UserOf(ActorId),
+    UserExpires(u64),
+    OwnersOf(Vec<ActorId>),
+    TokenMetadata(Option<TokenMetadata>),
+    IsMetadataFrozen(bool),
+    ParentOf(Option<(ActorId, U256)>),
+    ChildrenOf(Vec<U256>),
+    RootOwner(ActorId),
+    LockerOf(Option<ActorId>),
+    TokenHistory(Vec<OwnershipRecord>),
+    IsValid(bool),
+    ExpiresAt(Option<u64>),
 }
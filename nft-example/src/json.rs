@@ -0,0 +1,62 @@
+use gstd::prelude::*;
+use nft_example_io::{AttributeValue, TokenMetadata};
+
+const DATA_URI_PREFIX: &str = "data:application/json;base64,";
+
+/// Renders the token metadata as an ERC-721 metadata JSON document inside a base64 data URI
+/// Arguments:
+/// * `name`: the name used when the metadata has no title
+/// * `metadata`: the metadata of the token
+pub fn token_uri(name: &str, metadata: &TokenMetadata) -> String {
+    let mut json = String::from("{\"name\":");
+    push_string(&mut json, metadata.title.as_deref().unwrap_or(name));
+    if let Some(description) = &metadata.description {
+        json.push_str(",\"description\":");
+        push_string(&mut json, description);
+    }
+    if let Some(media) = &metadata.media {
+        json.push_str(",\"image\":");
+        push_string(&mut json, media);
+    }
+    if let Some(reference) = &metadata.reference {
+        json.push_str(",\"external_url\":");
+        push_string(&mut json, reference);
+    }
+    json.push_str(",\"attributes\":[");
+    for (i, (trait_type, value)) in metadata.attributes.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"trait_type\":");
+        push_string(&mut json, trait_type);
+        json.push_str(",\"value\":");
+        match value {
+            AttributeValue::Text(text) => push_string(&mut json, text),
+            AttributeValue::Number(number) => json.push_str(&number.to_string()),
+            AttributeValue::Boolean(boolean) => json.push_str(&boolean.to_string()),
+        }
+        json.push('}');
+    }
+    json.push_str("]}");
+
+    let mut uri = String::from(DATA_URI_PREFIX);
+    uri.push_str(&base64::encode(json));
+    uri
+}
+
+/// Appends `value` as a quoted and escaped JSON string
+fn push_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
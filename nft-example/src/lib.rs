@@ -1,27 +1,33 @@
 #![no_std]
 #![feature(const_btree_new)]
 
-use codec::{Decode, Encode};
+use codec::Encode;
 use gstd::{debug, exec, msg, prelude::*, ActorId};
+use nft_example_io::{
+    Action, AttributeValue, Event, ExpiryConfig, InitConfig, PresaleConfig, RoyaltyChange,
+    SaleRecord, State, StateReply, TokenMetadata,
+};
 use primitive_types::U256;
-use scale_info::TypeInfo;
-use nft_example_io::*;
-use nft_example_io::{Action, Event};
-
+use sp_core_hashing::blake2_256;
 
 use non_fungible_token::base::NonFungibleTokenBase;
+use non_fungible_token::composable::NonFungibleTokenComposable;
+use non_fungible_token::lock::NonFungibleTokenLockable;
+use non_fungible_token::metadata::NonFungibleTokenMetadata;
+use non_fungible_token::provenance::NonFungibleTokenProvenance;
+use non_fungible_token::rental::NonFungibleTokenRental;
+use non_fungible_token::royalty::{NonFungibleTokenRoyalty, MAX_BASIS_POINTS};
+use non_fungible_token::soulbound::NonFungibleTokenSoulbound;
 use non_fungible_token::NonFungibleToken;
 
+mod json;
+
 const GAS_RESERVE: u64 = 500_000_000;
 const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
-// const ROYALTY_MULTIPLIER: u64 = 5; // fixed royalty %? 
-
-#[derive(Debug, Decode, TypeInfo)]
-pub struct InitConfig {
-    pub name: String,
-    pub symbol: String,
-    pub base_uri: String,
-}
+// royalty rate in basis points applied to newly minted tokens
+const DEFAULT_ROYALTY_RATE: u16 = 500;
+// the number of programs a root owner query can pass through, so nesting loops between programs end
+const MAX_ROOT_OWNER_HOPS: u32 = 8;
 
 #[derive(Debug)]
 pub struct NFT {
@@ -29,7 +35,27 @@ pub struct NFT {
     pub token_id: U256,
     pub owner: ActorId,
     pub origin_by_id: BTreeMap<U256, ActorId>,
-    pub royalty_rate: BTreeMap<U256, u64>, //token id:rate 
+    pub max_royalty_rate: u16,
+    pub royalty_history: BTreeMap<U256, Vec<RoyaltyChange>>,
+    pub sales_by_id: BTreeMap<U256, Vec<SaleRecord>>,
+    pub pending_owner: Option<ActorId>,
+    pub mint_paused: bool,
+    pub max_supply: Option<U256>,
+    pub mint_limit: Option<u32>,
+    pub minted_by: BTreeMap<ActorId, u32>,
+    pub mint_price: Option<u128>,
+    pub proceeds: u128,
+    pub presale: Option<PresaleConfig>,
+    pub presale_minted: BTreeMap<ActorId, u32>,
+    pub public_sale: bool,
+    pub unrevealed_uri: Option<String>,
+    pub provenance_hash: Option<[u8; 32]>,
+    pub soulbound_burnable: bool,
+    pub on_chain_uri: bool,
+    pub metadata_updaters: BTreeSet<ActorId>,
+    pub expiry: Option<ExpiryConfig>,
+    /// The timestamps in milliseconds when the tokens stop being valid
+    pub expires_by_id: BTreeMap<U256, u64>,
 }
 
 static mut CONTRACT: NFT = NFT {
@@ -37,83 +63,535 @@ static mut CONTRACT: NFT = NFT {
     token_id: U256::zero(),
     owner: ZERO_ID,
     origin_by_id: BTreeMap::new(),
-    royalty_rate: BTreeMap::new(),
+    max_royalty_rate: 0,
+    royalty_history: BTreeMap::new(),
+    sales_by_id: BTreeMap::new(),
+    pending_owner: None,
+    mint_paused: false,
+    max_supply: None,
+    mint_limit: None,
+    minted_by: BTreeMap::new(),
+    mint_price: None,
+    proceeds: 0,
+    presale: None,
+    presale_minted: BTreeMap::new(),
+    public_sale: true,
+    unrevealed_uri: None,
+    provenance_hash: None,
+    soulbound_burnable: false,
+    on_chain_uri: false,
+    metadata_updaters: BTreeSet::new(),
+    expiry: None,
+    expires_by_id: BTreeMap::new(),
 };
 
+/// Builds the allowlist leaf of `account` that is allowed to mint `max_allowed` tokens
+fn presale_leaf(account: &ActorId, max_allowed: u32) -> [u8; 32] {
+    let mut data = account.encode();
+    data.extend(max_allowed.encode());
+    blake2_256(&data)
+}
+
+/// Checks that `leaf` belongs to the Merkle tree with `root`
+/// The pairs of nodes are sorted before hashing, so the proof doesn't need to contain positions
+fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |computed, node| {
+        let mut data = Vec::with_capacity(64);
+        if computed <= *node {
+            data.extend_from_slice(&computed);
+            data.extend_from_slice(node);
+        } else {
+            data.extend_from_slice(node);
+            data.extend_from_slice(&computed);
+        }
+        blake2_256(&data)
+    });
+    computed == root
+}
+
 impl NFT {
     fn mint(&mut self) {
-        self.token.owner_by_id.insert(self.token_id, msg::source());
-        let balance = *self
-            .token
-            .balances
+        let refund = self.take_public_mint(1);
+        let token_id = self.mint_to(&msg::source());
+        msg::reply(
+            Event::Transfer {
+                from: ZERO_ID,
+                to: msg::source(),
+                token_id,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            refund,
+        );
+    }
+
+    /// Mints `count` tokens to `to` in a single message
+    /// The public sale restrictions and the mint price apply to each of the tokens
+    fn mint_batch(&mut self, count: u32, to: &ActorId) {
+        if count == 0 {
+            panic!("NonFungibleToken: Nothing to mint");
+        }
+        if to == &ZERO_ID {
+            panic!("NonFungibleToken: Mint to zero address");
+        }
+        let refund = self.take_public_mint(count);
+        let token_ids = (0..count).map(|_| self.mint_to(to)).collect();
+        msg::reply(
+            Event::TransferBatch {
+                from: ZERO_ID,
+                to: *to,
+                token_ids,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            refund,
+        );
+    }
+
+    /// Checks that `msg::source()` can mint `count` tokens in the public sale,
+    /// takes the payment for them and returns the overpaid value
+    fn take_public_mint(&mut self, count: u32) -> u128 {
+        if !self.public_sale {
+            panic!("NonFungibleToken: Public sale has not started");
+        }
+        self.check_mint(count);
+        let minted = self
+            .minted_by
             .get(&msg::source())
-            .unwrap_or(&U256::zero());
+            .unwrap_or(&0)
+            .saturating_add(count);
+        if let Some(mint_limit) = self.mint_limit {
+            if minted > mint_limit {
+                panic!("NonFungibleToken: Mint limit reached");
+            }
+        }
+        let price = self
+            .mint_price
+            .unwrap_or(0)
+            .checked_mul(count as u128)
+            .expect("NonFungibleToken: Mint price overflow");
+        let refund = self.take_payment(price);
+        self.minted_by.insert(msg::source(), minted);
+        refund
+    }
 
-        self.token
-            .balances
-            .insert(msg::source(), balance.saturating_add(U256::one()));
+    /// Mints a token to an allowlisted account during the presale
+    /// Arguments:
+    /// * `proof`: the Merkle proof of the leaf built from `msg::source()` and `max_allowed`
+    /// * `max_allowed`: the number of tokens the account is allowed to mint during the presale
+    fn presale_mint(&mut self, proof: Vec<[u8; 32]>, max_allowed: u32) {
+        let presale = self
+            .presale
+            .as_ref()
+            .expect("NonFungibleToken: Presale is not set");
+        let block_height = exec::block_height();
+        if self.public_sale
+            || block_height < presale.start_block
+            || block_height > presale.end_block
+        {
+            panic!("NonFungibleToken: Presale is not active");
+        }
+        let leaf = presale_leaf(&msg::source(), max_allowed);
+        if !verify_proof(&proof, presale.merkle_root, leaf) {
+            panic!("NonFungibleToken: Invalid Merkle proof");
+        }
+        self.check_mint(1);
+        let minted = *self.presale_minted.get(&msg::source()).unwrap_or(&0);
+        if minted >= max_allowed {
+            panic!("NonFungibleToken: Presale mint limit reached");
+        }
+        let refund = self.take_payment(self.mint_price.unwrap_or(0));
+        self.presale_minted
+            .insert(msg::source(), minted.saturating_add(1));
 
+        let token_id = self.mint_to(&msg::source());
         msg::reply(
             Event::Transfer {
                 from: ZERO_ID,
                 to: msg::source(),
-                token_id: self.token_id,
+                token_id,
             },
             exec::gas_available() - GAS_RESERVE,
-            0,
+            refund,
+        );
+    }
+
+    /// Checks the restrictions shared by the public sale and the presale
+    fn check_mint(&self, count: u32) {
+        if self.mint_paused {
+            panic!("NonFungibleToken: Minting is paused");
+        }
+        if let Some(max_supply) = self.max_supply {
+            if self.token_id.saturating_add(count.into()) > max_supply {
+                panic!("NonFungibleToken: Max supply reached");
+            }
+        }
+    }
+
+    /// Creates a new token owned by `to` and returns its ID
+    fn mint_to(&mut self, to: &ActorId) -> U256 {
+        let token_id = self.token_id;
+        self.token.owner_by_id.insert(token_id, *to);
+        let balance = *self.token.balances.get(to).unwrap_or(&U256::zero());
+        self.token
+            .balances
+            .insert(*to, balance.saturating_add(U256::one()));
+        self.origin_by_id.insert(token_id, *to);
+        self.token.record_ownership(token_id, &ZERO_ID, to, None);
+        if let Some(expiry) = &self.expiry {
+            let expires = exec::block_timestamp().saturating_add(expiry.period);
+            self.expires_by_id.insert(token_id, expires);
+        }
+        self.set_royalty(
+            token_id,
+            to,
+            DEFAULT_ROYALTY_RATE.min(self.max_royalty_rate),
         );
         self.token_id = self.token_id.saturating_add(U256::one());
-        self.origin_by_id.insert(self.token_id, msg::source());
+        token_id
     }
 
-    fn royalty(&mut self, token_id: U256, price: u64) {
-        if !self.token.exists(token_id) {
-            panic!("NonFungibleToken: Token does not exist");
+    /// Adds `price` to the collection proceeds and returns the overpaid value
+    /// Contract must panic if the value attached to the message is less than `price`
+    fn take_payment(&mut self, price: u128) -> u128 {
+        if msg::value() < price {
+            panic!("NonFungibleToken: Insufficient value for the payment");
         }
-        let multiplier = *self.royalty_rate.get(&token_id).unwrap_or(&5);
+        self.proceeds = self.proceeds.saturating_add(price);
+        msg::value() - price
+    }
+
+    fn withdraw(&mut self) {
+        self.only_owner();
+        let amount = self.proceeds;
+        if amount == 0 {
+            panic!("NonFungibleToken: Nothing to withdraw");
+        }
+        self.proceeds = 0;
+        msg::send_bytes(self.owner, b"", 0, amount);
         msg::reply(
-            Event::Royalty {
-                amount: price*multiplier/100,
-                origin: *self.origin_by_id.get(&token_id).unwrap_or(&ZERO_ID),
+            Event::Withdrawn {
+                to: self.owner,
+                amount,
             },
             0,
             0,
         );
+    }
 
-        // if self.royalty_rate.contains_key(&token_id) {
-        //     let multiplier = *self.royalty_rate.get(&token_id).unwrap_or(&0);
-        //     msg::reply(
-        //         Event::Royalty {
-        //             amount: price*multiplier/100,
-        //             origin: *self.origin_by_id.get(&token_id).unwrap_or(&ZERO_ID),
-        //         },
-        //         0,
-        //         0,
-        //     );
-        // } else {
-        //     msg::reply(
-        //         Event::Royalty {
-        //             amount: price*ROYALTY_MULTIPLIER/100,
-        //             origin: *self.origin_by_id.get(&token_id).unwrap_or(&ZERO_ID),
-        //         },
-        //         0,
-        //         0,
-        //     );
-    }
-
-    fn assignroyalty(&mut self, token_id: U256, rate: u64) {
+    fn royalty(&mut self, token_id: U256, price: u128) {
         if !self.token.exists(token_id) {
             panic!("NonFungibleToken: Token does not exist");
         }
+        let recipients = self.token.royalty_breakdown(token_id, price);
+        let amount = recipients.iter().map(|(_, amount)| amount).sum();
+        msg::reply(Event::Royalty { amount, recipients }, 0, 0);
+    }
+
+    /// Checks that the caller can change the royalty of the token and returns the token origin
+    fn only_royalty_manager(&self, token_id: U256) -> ActorId {
+        if !self.token.exists(token_id) {
+            panic!("NonFungibleToken: Token does not exist");
+        }
+        let origin = *self.origin_by_id.get(&token_id).unwrap_or(&ZERO_ID);
+        if msg::source() != origin && msg::source() != self.owner {
+            panic!("NonFungibleToken: Only the token origin or owner can assign royalty");
+        }
+        origin
+    }
+
+    fn assignroyalty(&mut self, token_id: U256, rate: u16, shares: Vec<(ActorId, u16)>) {
+        let origin = self.only_royalty_manager(token_id);
+        if rate > self.max_royalty_rate {
+            panic!("NonFungibleToken: Royalty rate exceeds the collection maximum");
+        }
+        if shares.iter().any(|(recipient, _)| recipient == &ZERO_ID) {
+            panic!("NonFungibleToken: Royalty share for a zero address");
+        }
+        let total = shares
+            .iter()
+            .try_fold(0u16, |total, (_, rate)| total.checked_add(*rate));
+        if !shares.is_empty() && total != Some(rate) {
+            panic!("NonFungibleToken: Royalty shares do not sum to the rate");
+        }
+        let shares = if shares.is_empty() {
+            self.token.set_token_royalty(token_id, &origin, rate);
+            vec![(origin, rate)]
+        } else {
+            self.token.set_royalty_shares(token_id, shares.clone());
+            shares
+        };
+        self.record_royalty_change(token_id, rate);
+        msg::reply(Event::AssignRoyalty { token_id, shares }, 0, 0);
+    }
+
+    /// Removes the royalty of the token, so the default royalty of the collection applies to it
+    fn reset_token_royalty(&mut self, token_id: U256) {
+        self.only_royalty_manager(token_id);
+        self.token.reset_token_royalty(token_id);
+        let rate = self
+            .token
+            .default_royalty
+            .as_ref()
+            .map(|royalty| royalty.rate)
+            .unwrap_or(0);
+        self.record_royalty_change(token_id, rate);
+        msg::reply(Event::TokenRoyaltyReset(token_id), 0, 0);
+    }
+
+    /// Sets the royalty applied to the tokens that have no royalty of their own
+    fn set_default_royalty(&mut self, receiver: &ActorId, rate: u16) {
+        self.only_owner();
+        if receiver == &ZERO_ID {
+            panic!("NonFungibleToken: Royalty for a zero address");
+        }
+        if rate > self.max_royalty_rate {
+            panic!("NonFungibleToken: Royalty rate exceeds the collection maximum");
+        }
+        self.token.set_default_royalty(receiver, rate);
         msg::reply(
-            Event::AssignRoyalty {
+            Event::DefaultRoyaltySet {
+                receiver: *receiver,
+                rate,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn delete_default_royalty(&mut self) {
+        self.only_owner();
+        self.token.delete_default_royalty();
+        msg::reply(Event::DefaultRoyaltyDeleted, 0, 0);
+    }
+
+    /// Sets the royalty of the token and records the change in its history
+    fn set_royalty(&mut self, token_id: U256, receiver: &ActorId, rate: u16) {
+        self.token.set_token_royalty(token_id, receiver, rate);
+        self.record_royalty_change(token_id, rate);
+    }
+
+    fn record_royalty_change(&mut self, token_id: U256, rate: u16) {
+        self.royalty_history
+            .entry(token_id)
+            .or_default()
+            .push(RoyaltyChange {
+                rate,
+                changed_by: msg::source(),
+                block_height: exec::block_height(),
+            });
+    }
+
+    /// Sells the token to `to` for the value attached to the message
+    /// The royalty is sent to its recipients, the rest of the value to the token owner
+    fn sale(&mut self, token_id: U256, to: &ActorId) {
+        let price = msg::value();
+        let seller = self.token.check_transfer(&msg::source(), to, token_id);
+        self.check_not_expired(token_id);
+        let royalties = self.token.royalty_breakdown(token_id, price);
+
+        let mut remainder = price;
+        for (recipient, amount) in royalties.iter() {
+            if *amount > 0 {
+                msg::send_bytes(*recipient, b"", 0, *amount);
+                remainder = remainder.saturating_sub(*amount);
+            }
+        }
+        if remainder > 0 {
+            msg::send_bytes(seller, b"", 0, remainder);
+        }
+
+        self.token.sell_token(&seller, to, token_id, price);
+        self.sales_by_id
+            .entry(token_id)
+            .or_default()
+            .push(SaleRecord {
+                seller,
+                buyer: *to,
+                price,
+                block_height: exec::block_height(),
+            });
+
+        msg::reply(
+            Event::Sale {
                 token_id,
-                recipient: *self.origin_by_id.get(&token_id).unwrap_or(&ZERO_ID),
+                seller,
+                buyer: *to,
+                price,
+                royalties,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    fn only_owner(&self) {
+        if msg::source() != self.owner {
+            panic!("NonFungibleToken: Only the collection owner can call this");
+        }
+    }
+
+    fn set_mint_paused(&mut self, paused: bool) {
+        self.only_owner();
+        self.mint_paused = paused;
+        let event = match paused {
+            true => Event::MintPaused,
+            false => Event::MintUnpaused,
+        };
+        msg::reply(event, 0, 0);
+    }
+
+    /// Sets the allowlist and the presale window, the public sale is closed until `start_public_sale`
+    fn set_presale(&mut self, presale: PresaleConfig) {
+        self.only_owner();
+        if presale.start_block > presale.end_block {
+            panic!("NonFungibleToken: Presale ends before it starts");
+        }
+        self.presale = Some(presale.clone());
+        self.public_sale = false;
+        msg::reply(Event::PresaleSet(presale), 0, 0);
+    }
+
+    fn start_public_sale(&mut self) {
+        self.only_owner();
+        self.public_sale = true;
+        msg::reply(Event::PublicSaleStarted, 0, 0);
+    }
+
+    /// Returns the placeholder URI until the collection is revealed.
+    /// Afterwards it returns the metadata JSON rendered into a data URI if the collection
+    /// keeps its metadata on-chain, or `base_uri` followed by the token ID otherwise
+    fn token_uri(&self, token_id: U256) -> String {
+        if let Some(unrevealed_uri) = &self.unrevealed_uri {
+            return unrevealed_uri.clone();
+        }
+        if !self.on_chain_uri {
+            return format!("{}{}", self.token.base_uri, token_id);
+        }
+        let metadata = self
+            .token
+            .token_metadata_by_id
+            .get(&token_id)
+            .cloned()
+            .unwrap_or_default();
+        json::token_uri(&format!("{} #{}", self.token.name, token_id), &metadata)
+    }
+
+    /// Replaces the placeholder URI with the real metadata location
+    /// Arguments:
+    /// * `base_uri`: the URI that the token IDs are appended to
+    /// * `provenance_hash`: the hash of the ordered metadata of the collection,
+    /// it must be the one committed at the initialization, so the owner can't reorder the metadata
+    /// after seeing who minted which token
+    fn reveal(&mut self, base_uri: String, provenance_hash: [u8; 32]) {
+        self.only_owner();
+        if self.unrevealed_uri.is_none() {
+            panic!("NonFungibleToken: Collection is already revealed");
+        }
+        if self.provenance_hash != Some(provenance_hash) {
+            panic!("NonFungibleToken: Provenance hash does not match the committed one");
+        }
+        self.unrevealed_uri = None;
+        self.token.base_uri = base_uri.clone();
+        msg::reply(
+            Event::Revealed {
+                base_uri,
+                provenance_hash,
             },
             0,
             0,
         );
-        self.royalty_rate.insert(self.token_id, rate);
+    }
+
+    fn set_base_uri(&mut self, base_uri: String) {
+        self.only_owner();
+        self.token.base_uri = base_uri.clone();
+        msg::reply(Event::BaseUriUpdated(base_uri), 0, 0);
+    }
+
+    fn set_max_supply(&mut self, max_supply: Option<U256>) {
+        self.only_owner();
+        if let Some(max_supply) = max_supply {
+            if max_supply < self.token_id {
+                panic!("NonFungibleToken: Max supply is less than the minted amount");
+            }
+        }
+        self.max_supply = max_supply;
+        msg::reply(Event::MaxSupplyUpdated(max_supply), 0, 0);
+    }
+
+    fn set_mint_limit(&mut self, mint_limit: Option<u32>) {
+        self.only_owner();
+        self.mint_limit = mint_limit;
+        msg::reply(Event::MintLimitUpdated(mint_limit), 0, 0);
+    }
+
+    /// Starts the transfer of the collection ownership
+    /// It's finished only when `pending_owner` accepts it
+    fn transfer_ownership(&mut self, pending_owner: &ActorId) {
+        self.only_owner();
+        if pending_owner == &ZERO_ID {
+            panic!("NonFungibleToken: Ownership transfer to zero address");
+        }
+        self.pending_owner = Some(*pending_owner);
+        msg::reply(
+            Event::OwnershipTransferStarted {
+                owner: self.owner,
+                pending_owner: *pending_owner,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn accept_ownership(&mut self) {
+        if self.pending_owner != Some(msg::source()) {
+            panic!("NonFungibleToken: Caller is not the pending owner");
+        }
+        let previous_owner = self.owner;
+        self.owner = msg::source();
+        self.pending_owner = None;
+        msg::reply(
+            Event::OwnershipTransferred {
+                previous_owner,
+                new_owner: self.owner,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Transfers several tokens of the same owner to `to`
+    /// The caller must be authorized for every token, otherwise none of them is transferred
+    fn transfer_batch(&mut self, to: &ActorId, token_ids: Vec<U256>) {
+        if token_ids.is_empty() {
+            panic!("NonFungibleToken: Nothing to transfer");
+        }
+        let owners: Vec<ActorId> = token_ids
+            .iter()
+            .map(|token_id| {
+                self.check_not_expired(*token_id);
+                self.token.check_transfer(&msg::source(), to, *token_id)
+            })
+            .collect();
+        let unique: BTreeSet<&U256> = token_ids.iter().collect();
+        if unique.len() != token_ids.len() {
+            panic!("NonFungibleToken: Duplicate token in batch");
+        }
+        let from = owners[0];
+        if owners.iter().any(|owner| owner != &from) {
+            panic!("NonFungibleToken: Tokens in batch have different owners");
+        }
+        for token_id in token_ids.iter() {
+            self.token.move_token(&from, to, *token_id);
+        }
+        msg::reply(
+            Event::TransferBatch {
+                from,
+                to: *to,
+                token_ids,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
     }
 
     fn burn(&mut self, token_id: U256) {
@@ -123,19 +601,54 @@ impl NFT {
         if !self.token.is_token_owner(token_id, &msg::source()) {
             panic!("NonFungibleToken: account is not owner");
         }
+        if self.token.is_soulbound(token_id) && !self.soulbound_burnable {
+            panic!("NonFungibleToken: Soulbound token can only be revoked by the issuer");
+        }
+        self.token.check_unlocked(token_id);
+        self.token.check_not_pending(token_id);
+        self.burn_token(token_id);
+    }
+
+    /// Burns a soulbound token of any holder, it's how the issuer revokes soulbound tokens
+    fn revoke(&mut self, token_id: U256) {
+        self.only_owner();
+        if !self.token.exists(token_id) {
+            panic!("NonFungibleToken: Token does not exist");
+        }
+        if !self.token.is_soulbound(token_id) {
+            panic!("NonFungibleToken: Only soulbound tokens can be revoked");
+        }
+        self.token.check_not_pending(token_id);
+        self.burn_token(token_id);
+    }
+
+    fn burn_token(&mut self, token_id: U256) {
+        let owner = *self.token.owner_by_id.get(&token_id).unwrap_or(&ZERO_ID);
+        // the nested tokens stay with the owner
+        for child in self.token.children_of(token_id) {
+            self.token.unlink(child);
+        }
+        self.token.unlink(token_id);
+        self.token.locked_by.remove(&token_id);
         self.token.token_approvals.remove(&token_id);
         self.token.owner_by_id.remove(&token_id);
-        let balance = *self
-            .token
-            .balances
-            .get(&msg::source())
-            .unwrap_or(&U256::zero());
+        self.token.soulbound_tokens.remove(&token_id);
+        self.token.user_by_id.remove(&token_id);
+        self.token.token_metadata_by_id.remove(&token_id);
+        self.token.frozen_metadata.remove(&token_id);
+        self.token.reset_token_royalty(token_id);
+        self.origin_by_id.remove(&token_id);
+        self.expires_by_id.remove(&token_id);
+        // the royalty and ownership histories are kept after the burn
+        self.token
+            .record_ownership(token_id, &owner, &ZERO_ID, None);
+        let balance = *self.token.balances.get(&owner).unwrap_or(&U256::zero());
         self.token
             .balances
-            .insert(msg::source(), balance.saturating_sub(U256::one()));
+            .insert(owner, balance.saturating_sub(U256::one()));
         msg::reply(
             Event::Transfer {
-                from: msg::source(),
+                from: owner,
                 to: ZERO_ID,
                 token_id,
             },
@@ -143,6 +656,197 @@ impl NFT {
             0,
         );
     }
+
+    /// Allows or forbids the actor to update the metadata of the tokens
+    fn set_metadata_updater(&mut self, updater: &ActorId, allowed: bool) {
+        self.only_owner();
+        match allowed {
+            true => self.metadata_updaters.insert(*updater),
+            false => self.metadata_updaters.remove(updater),
+        };
+        msg::reply(
+            Event::MetadataUpdaterSet {
+                updater: *updater,
+                allowed,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn only_metadata_updater(&self) {
+        if msg::source() != self.owner && !self.metadata_updaters.contains(&msg::source()) {
+            panic!("NonFungibleToken: Only the collection owner or updaters can change metadata");
+        }
+    }
+
+    fn set_metadata(&mut self, token_id: U256, metadata: TokenMetadata) {
+        self.only_metadata_updater();
+        self.token.set_metadata(token_id, metadata);
+        msg::reply(Event::MetadataUpdate(token_id), 0, 0);
+    }
+
+    fn set_attribute(&mut self, token_id: U256, key: String, value: AttributeValue) {
+        self.only_metadata_updater();
+        self.token.set_attribute(token_id, key, value);
+        msg::reply(Event::MetadataUpdate(token_id), 0, 0);
+    }
+
+    fn remove_attribute(&mut self, token_id: U256, key: String) {
+        self.only_metadata_updater();
+        self.token.remove_attribute(token_id, key);
+        msg::reply(Event::MetadataUpdate(token_id), 0, 0);
+    }
+
+    fn freeze_metadata(&mut self, token_id: U256) {
+        self.only_metadata_updater();
+        self.token.freeze_metadata(token_id);
+        msg::reply(Event::MetadataFrozen(token_id), 0, 0);
+    }
+
+    fn freeze_all_metadata(&mut self) {
+        self.only_owner();
+        self.token.freeze_all_metadata();
+        msg::reply(Event::AllMetadataFrozen, 0, 0);
+    }
+
+    fn nest(&mut self, token_id: U256, parent: (ActorId, U256)) {
+        self.check_not_expired(token_id);
+        self.token.nest(&msg::source(), token_id, parent);
+        msg::reply(Event::Nested { token_id, parent }, 0, 0);
+    }
+
+    /// Detaches the token for its root owner, who may own a token of another program the tree is nested into
+    async fn detach(&mut self, token_id: U256, to: &ActorId) {
+        let root_owner = self.resolve_root_owner(token_id, 0).await;
+        self.check_not_expired(token_id);
+        self.token.detach(&msg::source(), &root_owner, token_id, to);
+        msg::reply(Event::Detached { token_id, to: *to }, 0, 0);
+    }
+
+    /// Returns the owner of the topmost token asking the programs the tree is nested into
+    /// Arguments:
+    /// * `hops`: the number of programs already asked, it stops the loops of programs nested into each other
+    async fn resolve_root_owner(&self, token_id: U256, hops: u32) -> ActorId {
+        if !self.token.exists(token_id) {
+            panic!("NonFungibleToken: Token does not exist");
+        }
+        let root = self.token.root_token(token_id);
+        match self.token.parent_of(root) {
+            Some((program, parent_id)) => {
+                if hops >= MAX_ROOT_OWNER_HOPS {
+                    panic!("NonFungibleToken: Too many programs in the nesting tree");
+                }
+                let reply: Event = msg::send_and_wait_for_reply(
+                    program,
+                    Action::ResolveRootOwner {
+                        token_id: parent_id,
+                        hops: hops + 1,
+                    },
+                    exec::gas_available().saturating_sub(GAS_RESERVE),
+                    0,
+                )
+                .await
+                .expect("NonFungibleToken: Error in getting the root owner");
+                match reply {
+                    Event::RootOwner(owner) => owner,
+                    _ => panic!("NonFungibleToken: Unexpected reply from the parent program"),
+                }
+            }
+            None => self.token.root_owner(token_id),
+        }
+    }
+
+    fn lock(&mut self, token_id: U256, locker: &ActorId) {
+        self.token.lock(&msg::source(), token_id, locker);
+        msg::reply(
+            Event::Locked {
+                token_id,
+                locker: *locker,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn unlock(&mut self, token_id: U256) {
+        self.token.unlock(&msg::source(), token_id);
+        msg::reply(Event::Unlocked(token_id), 0, 0);
+    }
+
+    fn has_expired(&self, token_id: U256) -> bool {
+        match self.expires_by_id.get(&token_id) {
+            Some(expires) => *expires <= exec::block_timestamp(),
+            None => false,
+        }
+    }
+
+    /// Returns false if the token doesn't exist or has expired
+    fn is_valid(&self, token_id: U256) -> bool {
+        self.token.exists(token_id) && !self.has_expired(token_id)
+    }
+
+    /// Panics if the collection forbids transferring expired tokens
+    /// and the token or one of the tokens nested into it has expired
+    fn check_not_expired(&self, token_id: U256) {
+        match &self.expiry {
+            Some(expiry) if !expiry.transferable_when_expired => {}
+            _ => return,
+        }
+        if self.has_expired(token_id)
+            || self
+                .token
+                .descendants(token_id)
+                .iter()
+                .any(|child| self.has_expired(*child))
+        {
+            panic!("NonFungibleToken: Token has expired");
+        }
+    }
+
+    /// Extends the validity of the caller's token by `periods` periods,
+    /// the extension price of each period must be attached to the message
+    fn extend(&mut self, token_id: U256, periods: u32) {
+        let expiry = self
+            .expiry
+            .as_ref()
+            .expect("NonFungibleToken: Tokens do not expire");
+        if !self.token.is_token_owner(token_id, &msg::source()) {
+            panic!("NonFungibleToken: account is not owner");
+        }
+        if periods == 0 {
+            panic!("NonFungibleToken: Nothing to extend");
+        }
+        let duration = expiry
+            .period
+            .checked_mul(periods as u64)
+            .expect("NonFungibleToken: Extension overflow");
+        let price = expiry
+            .extension_price
+            .unwrap_or(0)
+            .checked_mul(periods as u128)
+            .expect("NonFungibleToken: Extension price overflow");
+        let refund = self.take_payment(price);
+
+        let now = exec::block_timestamp();
+        let expires = self.expires_by_id.entry(token_id).or_insert(now);
+        *expires = (*expires).max(now).saturating_add(duration);
+        let expires = *expires;
+        msg::reply(Event::Extended { token_id, expires }, 0, refund);
+    }
+
+    fn set_token_soulbound(&mut self, token_id: U256, soulbound: bool) {
+        self.only_owner();
+        self.token.set_token_soulbound(token_id, soulbound);
+        msg::reply(
+            Event::SoulboundUpdated {
+                token_id,
+                soulbound,
+            },
+            0,
+            0,
+        );
+    }
 }
 
 gstd::metadata! {
@@ -157,39 +861,205 @@ gstd::metadata! {
             output: StateReply,
 }
 
-#[no_mangle]
-pub unsafe extern "C" fn handle() {
+#[gstd::async_main]
+async fn main() {
     let action: Action = msg::load().expect("Could not load Action");
+    let nft = unsafe { &mut CONTRACT };
     match action {
         Action::Mint => {
-            CONTRACT.mint();
+            nft.mint();
         }
         Action::Royalty { token_id, price } => {
-            CONTRACT.royalty(token_id, price); //update the state of the contract by updating the royalty amount
+            nft.royalty(token_id, price); //update the state of the contract by updating the royalty amount
         }
         Action::Burn(amount) => {
-            CONTRACT.burn(amount);
+            nft.burn(amount);
         }
         Action::Transfer { to, token_id } => {
-            CONTRACT.token.transfer(&msg::source(), &to, token_id);
-
+            nft.check_not_expired(token_id);
+            nft.token.transfer(&msg::source(), &to, token_id);
+        }
+        Action::SafeTransfer {
+            to,
+            token_id,
+            data,
+            to_program,
+        } => {
+            nft.check_not_expired(token_id);
+            nft.token
+                .safe_transfer(&msg::source(), &to, token_id, data, to_program)
+                .await;
         }
         Action::Approve { to, token_id } => {
-            CONTRACT.token.approve(&msg::source(), &to, token_id);
+            nft.token.approve(&msg::source(), &to, token_id);
         }
         Action::ApproveForAll { to, approved } => {
-            CONTRACT
-                .token
-                .approve_for_all(&msg::source(), &to, approved);
+            nft.token.approve_for_all(&msg::source(), &to, approved);
         }
         Action::OwnerOf(input) => {
-            CONTRACT.token.owner_of(input);
+            nft.token.owner_of(input);
         }
         Action::BalanceOf(input) => {
-            CONTRACT.token.balance_of(&input);
+            nft.token.balance_of(&input);
+        }
+        Action::AssignRoyalty {
+            token_id,
+            rate,
+            shares,
+        } => {
+            nft.assignroyalty(token_id, rate, shares); //assigns a royalty rate to a token
+        }
+        Action::Sale { token_id, to } => {
+            nft.sale(token_id, &to);
+        }
+        Action::PauseMint => {
+            nft.set_mint_paused(true);
+        }
+        Action::UnpauseMint => {
+            nft.set_mint_paused(false);
+        }
+        Action::SetBaseUri(base_uri) => {
+            nft.set_base_uri(base_uri);
+        }
+        Action::SetMaxSupply(max_supply) => {
+            nft.set_max_supply(max_supply);
+        }
+        Action::SetMintLimit(mint_limit) => {
+            nft.set_mint_limit(mint_limit);
+        }
+        Action::TransferOwnership(pending_owner) => {
+            nft.transfer_ownership(&pending_owner);
+        }
+        Action::AcceptOwnership => {
+            nft.accept_ownership();
+        }
+        Action::Withdraw => {
+            nft.withdraw();
+        }
+        Action::SetPresale(presale) => {
+            nft.set_presale(presale);
+        }
+        Action::PresaleMint { proof, max_allowed } => {
+            nft.presale_mint(proof, max_allowed);
+        }
+        Action::StartPublicSale => {
+            nft.start_public_sale();
+        }
+        Action::TokenUri(token_id) => {
+            if !nft.token.exists(token_id) {
+                panic!("NonFungibleToken: Token does not exist");
+            }
+            msg::reply(Event::TokenUri(nft.token_uri(token_id)), 0, 0);
+        }
+        Action::Reveal {
+            base_uri,
+            provenance_hash,
+        } => {
+            nft.reveal(base_uri, provenance_hash);
+        }
+        Action::SetTokenSoulbound {
+            token_id,
+            soulbound,
+        } => {
+            nft.set_token_soulbound(token_id, soulbound);
+        }
+        Action::Revoke(token_id) => {
+            nft.revoke(token_id);
+        }
+        Action::SetUser {
+            token_id,
+            user,
+            expires,
+        } => {
+            nft.token.set_user(&msg::source(), token_id, &user, expires);
+            msg::reply(
+                Event::UpdateUser {
+                    token_id,
+                    user,
+                    expires,
+                },
+                0,
+                0,
+            );
+        }
+        Action::UserOf(token_id) => {
+            msg::reply(Event::UserOf(nft.token.user_of(token_id)), 0, 0);
+        }
+        Action::MintBatch { count, to } => {
+            nft.mint_batch(count, &to);
+        }
+        Action::TransferBatch { to, token_ids } => {
+            nft.transfer_batch(&to, token_ids);
+        }
+        Action::SetMetadataUpdater { updater, allowed } => {
+            nft.set_metadata_updater(&updater, allowed);
+        }
+        Action::SetMetadata { token_id, metadata } => {
+            nft.set_metadata(token_id, metadata);
+        }
+        Action::SetAttribute {
+            token_id,
+            key,
+            value,
+        } => {
+            nft.set_attribute(token_id, key, value);
+        }
+        Action::RemoveAttribute { token_id, key } => {
+            nft.remove_attribute(token_id, key);
+        }
+        Action::FreezeMetadata(token_id) => {
+            nft.freeze_metadata(token_id);
         }
-        Action::AssignRoyalty { token_id, rate } => {
-            CONTRACT.assignroyalty(token_id, rate); //assigns a royalty rate to a token
+        Action::FreezeAllMetadata => {
+            nft.freeze_all_metadata();
+        }
+        Action::Nest { token_id, parent } => {
+            nft.nest(token_id, parent);
+        }
+        Action::Detach { token_id, to } => {
+            nft.detach(token_id, &to).await;
+        }
+        Action::RootOwner(token_id) => {
+            let owner = nft.resolve_root_owner(token_id, 0).await;
+            msg::reply(Event::RootOwner(owner), 0, 0);
+        }
+        Action::Lock { token_id, locker } => {
+            nft.lock(token_id, &locker);
+        }
+        Action::Unlock(token_id) => {
+            nft.unlock(token_id);
+        }
+        Action::Extend { token_id, periods } => {
+            nft.extend(token_id, periods);
+        }
+        Action::SetDefaultRoyalty { receiver, rate } => {
+            nft.set_default_royalty(&receiver, rate);
+        }
+        Action::DeleteDefaultRoyalty => {
+            nft.delete_default_royalty();
+        }
+        Action::ResetTokenRoyalty(token_id) => {
+            nft.reset_token_royalty(token_id);
+        }
+        Action::RoyaltyHistory(token_id) => {
+            let history = nft
+                .royalty_history
+                .get(&token_id)
+                .cloned()
+                .unwrap_or_default();
+            msg::reply(Event::RoyaltyHistory(history), 0, 0);
+        }
+        Action::ResolveRootOwner { token_id, hops } => {
+            let owner = nft.resolve_root_owner(token_id, hops).await;
+            msg::reply(Event::RootOwner(owner), 0, 0);
+        }
+        Action::TokenHistory {
+            token_id,
+            offset,
+            limit,
+        } => {
+            let history = nft.token.token_history(token_id, offset, limit);
+            msg::reply(Event::TokenHistory(history), 0, 0);
         }
     }
 }
@@ -198,10 +1068,29 @@ pub unsafe extern "C" fn handle() {
 pub unsafe extern "C" fn init() {
     let config: InitConfig = msg::load().expect("Unable to decode InitConfig");
     debug!("NFT {:?}", config);
+    if config.max_royalty_rate > MAX_BASIS_POINTS {
+        panic!("NonFungibleToken: Royalty rate exceeds MAX_BASIS_POINTS");
+    }
     CONTRACT
         .token
-        .init(config.name, config.symbol, config.base_uri); 
+        .init(config.name, config.symbol, config.base_uri);
     CONTRACT.owner = msg::source();
+    CONTRACT.max_royalty_rate = config.max_royalty_rate;
+    CONTRACT.mint_price = config.mint_price;
+    if config.unrevealed_uri.is_some() && config.provenance_hash.is_none() {
+        panic!("NonFungibleToken: Hidden metadata requires a provenance hash");
+    }
+    CONTRACT.unrevealed_uri = config.unrevealed_uri;
+    CONTRACT.provenance_hash = config.provenance_hash;
+    CONTRACT.token.set_soulbound(config.soulbound);
+    CONTRACT.soulbound_burnable = config.soulbound_burnable;
+    CONTRACT.on_chain_uri = config.on_chain_uri;
+    if let Some(expiry) = &config.expiry {
+        if expiry.period == 0 {
+            panic!("NonFungibleToken: Expiry period must be greater than zero");
+        }
+    }
+    CONTRACT.expiry = config.expiry;
 }
 
 #[no_mangle]
@@ -232,6 +1121,97 @@ pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
                 .unwrap_or(&ZERO_ID);
             StateReply::GetApproved(*approved_address).encode()
         }
+        State::Sales(token_id) => {
+            let sales = CONTRACT
+                .sales_by_id
+                .get(&token_id)
+                .cloned()
+                .unwrap_or_default();
+            StateReply::Sales(sales).encode()
+        }
+        State::RoyaltyHistory(token_id) => {
+            let history = CONTRACT
+                .royalty_history
+                .get(&token_id)
+                .cloned()
+                .unwrap_or_default();
+            StateReply::RoyaltyHistory(history).encode()
+        }
+        State::Owner => StateReply::Owner(CONTRACT.owner).encode(),
+        State::PendingOwner => StateReply::PendingOwner(CONTRACT.pending_owner).encode(),
+        State::IsMintPaused => StateReply::IsMintPaused(CONTRACT.mint_paused).encode(),
+        State::BaseUri => StateReply::BaseUri(CONTRACT.token.base_uri.clone()).encode(),
+        State::MaxSupply => StateReply::MaxSupply(CONTRACT.max_supply).encode(),
+        State::MintLimit => StateReply::MintLimit(CONTRACT.mint_limit).encode(),
+        State::TotalMinted => StateReply::TotalMinted(CONTRACT.token_id).encode(),
+        State::MintedBy(account) => {
+            StateReply::MintedBy(*CONTRACT.minted_by.get(&account).unwrap_or(&0)).encode()
+        }
+        State::MintPrice => StateReply::MintPrice(CONTRACT.mint_price).encode(),
+        State::Proceeds => StateReply::Proceeds(CONTRACT.proceeds).encode(),
+        State::Presale => StateReply::Presale(CONTRACT.presale.clone()).encode(),
+        State::IsPublicSale => StateReply::IsPublicSale(CONTRACT.public_sale).encode(),
+        State::PresaleMintedBy(account) => {
+            StateReply::PresaleMintedBy(*CONTRACT.presale_minted.get(&account).unwrap_or(&0))
+                .encode()
+        }
+        State::TokenUri(token_id) => {
+            let uri = match CONTRACT.token.exists(token_id) {
+                true => CONTRACT.token_uri(token_id),
+                false => String::new(),
+            };
+            StateReply::TokenUri(uri).encode()
+        }
+        State::IsRevealed => StateReply::IsRevealed(CONTRACT.unrevealed_uri.is_none()).encode(),
+        State::ProvenanceHash => StateReply::ProvenanceHash(CONTRACT.provenance_hash).encode(),
+        State::IsSoulbound(token_id) => {
+            StateReply::IsSoulbound(CONTRACT.token.is_soulbound(token_id)).encode()
+        }
+        State::IsCollectionSoulbound => {
+            StateReply::IsCollectionSoulbound(CONTRACT.token.soulbound).encode()
+        }
+        State::UserOf(token_id) => StateReply::UserOf(CONTRACT.token.user_of(token_id)).encode(),
+        State::UserExpires(token_id) => {
+            StateReply::UserExpires(CONTRACT.token.user_expires(token_id)).encode()
+        }
+        State::OwnersOf(token_ids) => {
+            let owners = token_ids
+                .iter()
+                .map(|token_id| *CONTRACT.token.owner_by_id.get(token_id).unwrap_or(&ZERO_ID))
+                .collect();
+            StateReply::OwnersOf(owners).encode()
+        }
+        State::TokenMetadata(token_id) => {
+            let metadata = CONTRACT.token.token_metadata_by_id.get(&token_id).cloned();
+            StateReply::TokenMetadata(metadata).encode()
+        }
+        State::IsMetadataFrozen(token_id) => {
+            StateReply::IsMetadataFrozen(CONTRACT.token.is_metadata_frozen(token_id)).encode()
+        }
+        State::ParentOf(token_id) => {
+            StateReply::ParentOf(CONTRACT.token.parent_of(token_id)).encode()
+        }
+        State::ChildrenOf(token_id) => {
+            StateReply::ChildrenOf(CONTRACT.token.children_of(token_id)).encode()
+        }
+        State::RootOwner(token_id) => {
+            StateReply::RootOwner(CONTRACT.token.root_owner(token_id)).encode()
+        }
+        State::LockerOf(token_id) => {
+            StateReply::LockerOf(CONTRACT.token.locker_of(token_id)).encode()
+        }
+        State::TokenHistory {
+            token_id,
+            offset,
+            limit,
+        } => {
+            let history = CONTRACT.token.token_history(token_id, offset, limit);
+            StateReply::TokenHistory(history).encode()
+        }
+        State::IsValid(token_id) => StateReply::IsValid(CONTRACT.is_valid(token_id)).encode(),
+        State::ExpiresAt(token_id) => {
+            StateReply::ExpiresAt(CONTRACT.expires_by_id.get(&token_id).copied()).encode()
+        }
     };
     let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
 
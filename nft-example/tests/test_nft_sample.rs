@@ -1,6 +1,9 @@
-use codec::Encode;
+use codec::{Decode, Encode};
+use gstd::ActorId;
 use gtest::{Program, System};
 use nft_example_io::*;
+use non_fungible_token::provenance::MAX_HISTORY_LEN;
+use sp_core_hashing::blake2_256;
 
 const USERS: &'static [u64] = &[3, 4, 5];
 
@@ -17,7 +20,8 @@ fn init_with_mint<'a>(sys: &'a System) {
         InitConfig {
             name: String::from("MyToken"),
             symbol: String::from("MTK"),
-            base_uri: String::from(""),
+            max_royalty_rate: 1_000,
+            ..Default::default()
         },
     );
 
@@ -46,41 +50,1748 @@ fn royalty() {
     let sys = System::new();
     init_with_mint(&sys);
     let nft = sys.get_program(1);
-    let res = nft.send(USERS[0], :Action::Royalty {0.into(), 0.into()});
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: 0,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: 0,
+            recipients: vec![(USERS[0].into(), 0)],
+        }
+        .encode()
+    )));
+
+    // the default rate is 500 basis points
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: 10_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: 500,
+            recipients: vec![(USERS[0].into(), 500)],
+        }
+        .encode()
+    )));
+
+    // must not overflow on huge prices
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: u128::MAX,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: u128::MAX / 20,
+            recipients: vec![(USERS[0].into(), u128::MAX / 20)],
+        }
+        .encode()
+    )));
+}
+
+#[test]
+fn royalty_failures() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 1.into(),
+            price: 0,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn assignroyalty() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 0,
+            shares: vec![],
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::AssignRoyalty {
+            token_id: 0_i32.into(),
+            shares: vec![(USERS[0].into(), 0)],
+        }
+        .encode()
+    )));
+}
+
+#[test]
+fn assignroyalty_failures() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the caller is neither the token origin nor the collection owner
+    let res = nft.send(
+        USERS[1],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 100,
+            shares: vec![],
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the rate exceeds the collection maximum
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 1_001,
+            shares: vec![],
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the token doesn't exist
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 1.into(),
+            rate: 100,
+            shares: vec![],
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn default_royalty() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the caller isn't the collection owner
+    let res = nft.send(
+        USERS[1],
+        Action::SetDefaultRoyalty {
+            receiver: USERS[2].into(),
+            rate: 1_000,
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the rate exceeds the collection maximum
+    let res = nft.send(
+        USERS[0],
+        Action::SetDefaultRoyalty {
+            receiver: USERS[2].into(),
+            rate: 1_001,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[0],
+        Action::SetDefaultRoyalty {
+            receiver: USERS[2].into(),
+            rate: 1_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::DefaultRoyaltySet {
+            receiver: USERS[2].into(),
+            rate: 1_000,
+        }
+        .encode()
+    )));
+    // the royalty of the token overrides the default one
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: 10_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: 500,
+            recipients: vec![(USERS[0].into(), 500)],
+        }
+        .encode()
+    )));
+
+    // must fail since the caller is neither the token origin nor the collection owner
+    let res = nft.send(USERS[1], Action::ResetTokenRoyalty(0.into()));
+    assert!(res.main_failed());
+    let res = nft.send(USERS[0], Action::ResetTokenRoyalty(0.into()));
+    assert!(res.contains(&(USERS[0], Event::TokenRoyaltyReset(0.into()).encode())));
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: 10_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: 1_000,
+            recipients: vec![(USERS[2].into(), 1_000)],
+        }
+        .encode()
+    )));
+
+    let res = nft.send(USERS[0], Action::DeleteDefaultRoyalty);
+    assert!(res.contains(&(USERS[0], Event::DefaultRoyaltyDeleted.encode())));
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: 10_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: 0,
+            recipients: vec![(0.into(), 0)],
+        }
+        .encode()
+    )));
+}
+
+#[test]
+fn royalty_shares() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let shares = vec![(USERS[0].into(), 300), (USERS[1].into(), 200)];
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 500,
+            shares: shares.clone(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::AssignRoyalty {
+            token_id: 0.into(),
+            shares,
+        }
+        .encode()
+    )));
+
+    let res = nft.send(
+        USERS[0],
+        Action::Royalty {
+            token_id: 0.into(),
+            price: 10_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Royalty {
+            amount: 500,
+            recipients: vec![(USERS[0].into(), 300), (USERS[1].into(), 200)],
+        }
+        .encode()
+    )));
+
+    // must fail since the shares do not sum to the rate
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 500,
+            shares: vec![(USERS[0].into(), 300), (USERS[1].into(), 100)],
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn royalty_history() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 700,
+            shares: vec![],
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[0], Action::Burn(0.into()));
+    assert!(!res.main_failed());
+
+    // the history is kept after the burn
+    let res = nft.send(USERS[1], Action::RoyaltyHistory(0.into()));
+    let history = res
+        .log()
+        .iter()
+        .find_map(|log| match Event::decode(&mut log.payload()) {
+            Ok(Event::RoyaltyHistory(history)) => Some(history),
+            _ => None,
+        })
+        .expect("No royalty history in the reply");
+    let changes: Vec<(u16, ActorId)> = history
+        .iter()
+        .map(|change| (change.rate, change.changed_by))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![(500, USERS[0].into()), (700, USERS[0].into())]
+    );
+}
+
+#[test]
+fn sale() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(
+        USERS[0],
+        Action::AssignRoyalty {
+            token_id: 0.into(),
+            rate: 500,
+            shares: vec![(USERS[2].into(), 500)],
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Approve {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = nft.send_with_value(
+        USERS[1],
+        Action::Sale {
+            token_id: 0_i32.into(),
+            to: USERS[1].into(),
+        },
+        10_000,
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Sale {
+            token_id: 0_i32.into(),
+            seller: USERS[0].into(),
+            buyer: USERS[1].into(),
+            price: 10_000,
+            royalties: vec![(USERS[2].into(), 500)],
+        }
+        .encode()
+    )));
+    // the royalty receiver and the seller are paid
+    assert!(res.contains(&(USERS[2], Vec::<u8>::new())));
+    assert!(res.contains(&(USERS[0], Vec::<u8>::new())));
+
+    let res = nft.send(USERS[0], Action::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[1].into()).encode())));
+
+    // must fail since the caller is no longer an authorized source
+    let res = nft.send_with_value(
+        USERS[2],
+        Action::Sale {
+            token_id: 0_i32.into(),
+            to: USERS[2].into(),
+        },
+        10_000,
+    );
+    assert!(res.main_failed());
+
+    // a free sale moves the token without paying anyone
+    let res = nft.send(
+        USERS[1],
+        Action::Approve {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[2],
+        Action::Sale {
+            token_id: 0_i32.into(),
+            to: USERS[2].into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[2],
+        Event::Sale {
+            token_id: 0_i32.into(),
+            seller: USERS[1].into(),
+            buyer: USERS[2].into(),
+            price: 0,
+            royalties: vec![(USERS[2].into(), 0)],
+        }
+        .encode()
+    )));
+    assert!(!res.contains(&(USERS[1], Vec::<u8>::new())));
+    assert!(!res.contains(&(USERS[2], Vec::<u8>::new())));
+}
+
+#[test]
+fn pause_mint() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the caller isn't the collection owner
+    let res = nft.send(USERS[1], Action::PauseMint);
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[0], Action::PauseMint);
+    assert!(res.contains(&(USERS[0], Event::MintPaused.encode())));
+    // must fail since minting is paused
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[0], Action::UnpauseMint);
+    assert!(res.contains(&(USERS[0], Event::MintUnpaused.encode())));
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn mint_limits() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(USERS[0], Action::SetMintLimit(Some(1)));
+    assert!(res.contains(&(USERS[0], Event::MintLimitUpdated(Some(1)).encode())));
+    // must fail since `USERS[0]` has already minted a token
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[0], Action::SetMaxSupply(Some(2_i32.into())));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::MaxSupplyUpdated(Some(2_i32.into())).encode()
+    )));
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(!res.main_failed());
+    // must fail since the max supply is reached
+    let res = nft.send(USERS[2], Action::Mint);
+    assert!(res.main_failed());
+}
+
+#[test]
+fn transfer_ownership() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(USERS[0], Action::TransferOwnership(USERS[1].into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::OwnershipTransferStarted {
+            owner: USERS[0].into(),
+            pending_owner: USERS[1].into(),
+        }
+        .encode()
+    )));
+
+    // must fail since the caller isn't the pending owner
+    let res = nft.send(USERS[2], Action::AcceptOwnership);
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[1], Action::AcceptOwnership);
+    assert!(res.contains(&(
+        USERS[1],
+        Event::OwnershipTransferred {
+            previous_owner: USERS[0].into(),
+            new_owner: USERS[1].into(),
+        }
+        .encode()
+    )));
+
+    // must fail since `USERS[0]` is no longer the collection owner
+    let res = nft.send(USERS[0], Action::SetBaseUri(String::from("ipfs://")));
+    assert!(res.main_failed());
+    let res = nft.send(USERS[1], Action::SetBaseUri(String::from("ipfs://")));
+    assert!(res.contains(&(
+        USERS[1],
+        Event::BaseUriUpdated(String::from("ipfs://")).encode()
+    )));
+}
+
+#[test]
+fn paid_mint() {
+    let sys = System::new();
+    sys.init_logger();
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            mint_price: Some(1_000),
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+
+    // must fail since the attached value is less than the price
+    let res = nft.send_with_value(USERS[1], Action::Mint, 999);
+    assert!(res.main_failed());
+
+    let res = nft.send_with_value(USERS[1], Action::Mint, 1_000);
+    assert!(!res.main_failed());
+    let res = nft.send_with_value(USERS[1], Action::Mint, 1_500);
+    assert!(!res.main_failed());
+
+    // must fail since the caller isn't the collection owner
+    let res = nft.send(USERS[1], Action::Withdraw);
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[0], Action::Withdraw);
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Withdrawn {
+            to: USERS[0].into(),
+            amount: 2_000,
+        }
+        .encode()
+    )));
+
+    // must fail since the proceeds have been withdrawn
+    let res = nft.send(USERS[0], Action::Withdraw);
+    assert!(res.main_failed());
+}
+
+fn presale_leaf(user: u64, max_allowed: u32) -> [u8; 32] {
+    let account: ActorId = user.into();
+    blake2_256(&(account, max_allowed).encode())
+}
+
+fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    blake2_256(&[a, b].concat())
+}
+
+#[test]
+fn presale_mint() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let leaf_1 = presale_leaf(USERS[1], 2);
+    let leaf_2 = presale_leaf(USERS[2], 1);
+    let presale = PresaleConfig {
+        merkle_root: hash_pair(leaf_1, leaf_2),
+        start_block: 0,
+        end_block: 100,
+    };
+
+    // must fail since the caller isn't the collection owner
+    let res = nft.send(USERS[1], Action::SetPresale(presale.clone()));
+    assert!(res.main_failed());
+    let res = nft.send(USERS[0], Action::SetPresale(presale.clone()));
+    assert!(res.contains(&(USERS[0], Event::PresaleSet(presale).encode())));
+
+    // must fail since the public sale is closed during the presale
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[1],
+        Action::PresaleMint {
+            proof: vec![leaf_2],
+            max_allowed: 2,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Transfer {
+            from: 0.into(),
+            to: USERS[1].into(),
+            token_id: 1_i32.into(),
+        }
+        .encode()
+    )));
+
+    // must fail since `USERS[1]` isn't allowed to mint 3 tokens
+    let res = nft.send(
+        USERS[1],
+        Action::PresaleMint {
+            proof: vec![leaf_2],
+            max_allowed: 3,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[2],
+        Action::PresaleMint {
+            proof: vec![leaf_1],
+            max_allowed: 1,
+        },
+    );
+    assert!(!res.main_failed());
+    // must fail since `USERS[2]` has minted all allowed tokens
+    let res = nft.send(
+        USERS[2],
+        Action::PresaleMint {
+            proof: vec![leaf_1],
+            max_allowed: 1,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[0], Action::StartPublicSale);
+    assert!(res.contains(&(USERS[0], Event::PublicSaleStarted.encode())));
+    let res = nft.send(USERS[2], Action::Mint);
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn reveal() {
+    let sys = System::new();
+    sys.init_logger();
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            unrevealed_uri: Some(String::from("ipfs://hidden")),
+            provenance_hash: Some([1; 32]),
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(!res.main_failed());
+
+    let res = nft.send(USERS[1], Action::TokenUri(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[1],
+        Event::TokenUri(String::from("ipfs://hidden")).encode()
+    )));
+
+    // must fail since the caller isn't the collection owner
+    let res = nft.send(
+        USERS[1],
+        Action::Reveal {
+            base_uri: String::from("ipfs://revealed/"),
+            provenance_hash: [1; 32],
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the provenance hash differs from the committed one
+    let res = nft.send(
+        USERS[0],
+        Action::Reveal {
+            base_uri: String::from("ipfs://revealed/"),
+            provenance_hash: [2; 32],
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[0],
+        Action::Reveal {
+            base_uri: String::from("ipfs://revealed/"),
+            provenance_hash: [1; 32],
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Revealed {
+            base_uri: String::from("ipfs://revealed/"),
+            provenance_hash: [1; 32],
+        }
+        .encode()
+    )));
+
+    let res = nft.send(USERS[1], Action::TokenUri(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[1],
+        Event::TokenUri(String::from("ipfs://revealed/0")).encode()
+    )));
+
+    // must fail since the collection is already revealed
+    let res = nft.send(
+        USERS[0],
+        Action::Reveal {
+            base_uri: String::from("ipfs://other/"),
+            provenance_hash: [1; 32],
+        },
+    );
+    assert!(res.main_failed());
+
+    let sys = System::new();
+    sys.init_logger();
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    // must fail since the hidden metadata comes without the provenance hash
+    let res = nft.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            unrevealed_uri: Some(String::from("ipfs://hidden")),
+            ..Default::default()
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn soulbound() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(!res.main_failed());
+
+    // must fail since the caller isn't the collection owner
+    let res = nft.send(
+        USERS[1],
+        Action::SetTokenSoulbound {
+            token_id: 1_i32.into(),
+            soulbound: true,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[0],
+        Action::SetTokenSoulbound {
+            token_id: 1_i32.into(),
+            soulbound: true,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::SoulboundUpdated {
+            token_id: 1_i32.into(),
+            soulbound: true,
+        }
+        .encode()
+    )));
+
+    // must fail since the token is soulbound
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Approve {
+            to: USERS[2].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the holder isn't allowed to burn soulbound tokens
+    let res = nft.send(USERS[1], Action::Burn(1_i32.into()));
+    assert!(res.main_failed());
+
+    // an operator can't move the soulbound token either
+    let res = nft.send(
+        USERS[1],
+        Action::ApproveForAll {
+            to: USERS[2].into(),
+            approved: true,
+        },
+    );
+    assert!(!res.main_failed());
+    // must fail since the token is soulbound
+    let res = nft.send(
+        USERS[2],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the token isn't soulbound
+    let res = nft.send(USERS[0], Action::Revoke(0_i32.into()));
+    assert!(res.main_failed());
+
+    // the issuer revokes the token
+    let res = nft.send(USERS[0], Action::Revoke(1_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Transfer {
+            from: USERS[1].into(),
+            to: 0.into(),
+            token_id: 1_i32.into(),
+        }
+        .encode()
+    )));
+}
+
+#[test]
+fn soulbound_collection() {
+    let sys = System::new();
+    sys.init_logger();
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            soulbound: true,
+            soulbound_burnable: true,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[1], Action::Mint);
+    assert!(!res.main_failed());
+
+    // must fail since the whole collection is soulbound
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::ApproveForAll {
+            to: USERS[2].into(),
+            approved: true,
+        },
+    );
+    assert!(res.main_failed());
+
+    // the holder can burn the token since the collection is soulbound burnable
+    let res = nft.send(USERS[1], Action::Burn(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Transfer {
+            from: USERS[1].into(),
+            to: 0.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+}
+
+#[test]
+fn rental() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the caller is neither the owner nor an approved actor
+    let res = nft.send(
+        USERS[1],
+        Action::SetUser {
+            token_id: 0_i32.into(),
+            user: USERS[1].into(),
+            expires: u64::MAX,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[0],
+        Action::SetUser {
+            token_id: 0_i32.into(),
+            user: USERS[1].into(),
+            expires: u64::MAX,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::UpdateUser {
+            token_id: 0_i32.into(),
+            user: USERS[1].into(),
+            expires: u64::MAX,
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[0], Action::UserOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::UserOf(USERS[1].into()).encode())));
+
+    // the user role is cleared on transfer
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[0], Action::UserOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::UserOf(0.into()).encode())));
+
+    // the user role expires
+    let res = nft.send(
+        USERS[2],
+        Action::SetUser {
+            token_id: 0_i32.into(),
+            user: USERS[1].into(),
+            expires: 0,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[0], Action::UserOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::UserOf(0.into()).encode())));
+}
+
+#[test]
+fn batch() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(
+        USERS[0],
+        Action::MintBatch {
+            count: 3,
+            to: USERS[1].into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferBatch {
+            from: 0.into(),
+            to: USERS[1].into(),
+            token_ids: vec![1_i32.into(), 2_i32.into(), 3_i32.into()],
+        }
+        .encode()
+    )));
+
+    // must fail since `USERS[1]` doesn't own the token 0, so nothing is transferred
+    let res = nft.send(
+        USERS[1],
+        Action::TransferBatch {
+            to: USERS[2].into(),
+            token_ids: vec![1_i32.into(), 0_i32.into()],
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(USERS[0], Action::OwnerOf(1_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[1].into()).encode())));
+
+    let res = nft.send(
+        USERS[1],
+        Action::TransferBatch {
+            to: USERS[2].into(),
+            token_ids: vec![1_i32.into(), 3_i32.into()],
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::TransferBatch {
+            from: USERS[1].into(),
+            to: USERS[2].into(),
+            token_ids: vec![1_i32.into(), 3_i32.into()],
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[0], Action::BalanceOf(USERS[2].into()));
+    assert!(res.contains(&(USERS[0], Event::BalanceOf(2_i32.into()).encode())));
+
+    // an operator transfers the tokens, so the event names their owner
+    for user in [USERS[1], USERS[2]] {
+        let res = nft.send(
+            user,
+            Action::ApproveForAll {
+                to: USERS[0].into(),
+                approved: true,
+            },
+        );
+        assert!(!res.main_failed());
+    }
+    // must fail since the tokens belong to different owners
+    let res = nft.send(
+        USERS[0],
+        Action::TransferBatch {
+            to: USERS[0].into(),
+            token_ids: vec![1_i32.into(), 2_i32.into()],
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::TransferBatch {
+            to: USERS[0].into(),
+            token_ids: vec![1_i32.into(), 3_i32.into()],
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferBatch {
+            from: USERS[2].into(),
+            to: USERS[0].into(),
+            token_ids: vec![1_i32.into(), 3_i32.into()],
+        }
+        .encode()
+    )));
+
+    // must fail since the batch exceeds the mint limit
+    let res = nft.send(USERS[0], Action::SetMintLimit(Some(5)));
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::MintBatch {
+            count: 2,
+            to: USERS[0].into(),
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn metadata() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the caller is neither the collection owner nor an updater
+    let res = nft.send(
+        USERS[1],
+        Action::SetAttribute {
+            token_id: 0_i32.into(),
+            key: String::from("level"),
+            value: AttributeValue::Number(1),
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(
+        USERS[0],
+        Action::SetMetadataUpdater {
+            updater: USERS[1].into(),
+            allowed: true,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::SetAttribute {
+            token_id: 0_i32.into(),
+            key: String::from("level"),
+            value: AttributeValue::Number(2),
+        },
+    );
+    assert!(res.contains(&(USERS[1], Event::MetadataUpdate(0_i32.into()).encode())));
+
+    let res = nft.send(USERS[1], Action::FreezeMetadata(0_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::MetadataFrozen(0_i32.into()).encode())));
+
+    // must fail since the metadata is frozen
+    let res = nft.send(
+        USERS[1],
+        Action::SetAttribute {
+            token_id: 0_i32.into(),
+            key: String::from("level"),
+            value: AttributeValue::Number(3),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::SetMetadata {
+            token_id: 0_i32.into(),
+            metadata: TokenMetadata::default(),
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn on_chain_uri() {
+    let sys = System::new();
+    sys.init_logger();
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            on_chain_uri: true,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+
+    let json = r#"{"name":"MyToken #0","attributes":[]}"#;
+    let res = nft.send(USERS[0], Action::TokenUri(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TokenUri(format!(
+            "data:application/json;base64,{}",
+            base64::encode(json)
+        ))
+        .encode()
+    )));
+
+    let res = nft.send(
+        USERS[0],
+        Action::SetMetadata {
+            token_id: 0_i32.into(),
+            metadata: TokenMetadata {
+                title: Some(String::from("The \"First\" One")),
+                description: Some(String::from("Genesis token")),
+                media: Some(String::from("ipfs://image")),
+                reference: None,
+                attributes: vec![
+                    (
+                        String::from("color"),
+                        AttributeValue::Text(String::from("red")),
+                    ),
+                    (String::from("level"), AttributeValue::Number(-3)),
+                    (String::from("shiny"), AttributeValue::Boolean(true)),
+                ],
+            },
+        },
+    );
+    assert!(!res.main_failed());
+
+    let json = concat!(
+        r#"{"name":"The \"First\" One","description":"Genesis token","image":"ipfs://image","#,
+        r#""attributes":[{"trait_type":"color","value":"red"},"#,
+        r#"{"trait_type":"level","value":-3},{"trait_type":"shiny","value":true}]}"#
+    );
+    let res = nft.send(USERS[0], Action::TokenUri(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TokenUri(format!(
+            "data:application/json;base64,{}",
+            base64::encode(json)
+        ))
+        .encode()
+    )));
+}
+
+#[test]
+fn nested() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+
+    // the token 2 is nested into the token 1 that is nested into the token 0
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 1_i32.into(),
+            parent: (1.into(), 0_i32.into()),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Nested {
+            token_id: 1_i32.into(),
+            parent: (1.into(), 0_i32.into()),
+        }
+        .encode()
+    )));
+    // must fail since the caller isn't the token owner
+    let res = nft.send(
+        USERS[1],
+        Action::Nest {
+            token_id: 2_i32.into(),
+            parent: (1.into(), 1_i32.into()),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 2_i32.into(),
+            parent: (1.into(), 1_i32.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    // must fail since the parent is nested into the token
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 0_i32.into(),
+            parent: (1.into(), 2_i32.into()),
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the nested token can't be transferred
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: USERS[1].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // the whole tree moves with the root token
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[0], Action::OwnerOf(2_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[1].into()).encode())));
+    let res = nft.send(USERS[0], Action::BalanceOf(USERS[1].into()));
+    assert!(res.contains(&(USERS[0], Event::BalanceOf(3_i32.into()).encode())));
+    let res = nft.send(USERS[0], Action::RootOwner(2_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::RootOwner(USERS[1].into()).encode())));
+
+    // must fail since the caller doesn't own the tree anymore
+    let res = nft.send(
+        USERS[0],
+        Action::Detach {
+            token_id: 1_i32.into(),
+            to: USERS[0].into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Detach {
+            token_id: 1_i32.into(),
+            to: USERS[2].into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Detached {
+            token_id: 1_i32.into(),
+            to: USERS[2].into(),
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[0], Action::OwnerOf(2_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[2].into()).encode())));
+    let res = nft.send(USERS[0], Action::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[1].into()).encode())));
+
+    // the root owner of a token nested into another program is resolved by that program
+    let items = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = items.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyCharacters"),
+            symbol: String::from("MCH"),
+            max_royalty_rate: 1_000,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = items.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[2],
+        Action::Nest {
+            token_id: 1_i32.into(),
+            parent: (2.into(), 0_i32.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[1], Action::OwnerOf(2_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::OwnerOf(2.into()).encode())));
+    let res = nft.send(USERS[1], Action::RootOwner(2_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::RootOwner(USERS[0].into()).encode())));
+
+    // the owner of the foreign parent is the root owner, so it can detach the token
+    let res = nft.send(
+        USERS[0],
+        Action::Detach {
+            token_id: 1_i32.into(),
+            to: USERS[0].into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Detached {
+            token_id: 1_i32.into(),
+            to: USERS[0].into(),
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[1], Action::OwnerOf(2_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::OwnerOf(USERS[0].into()).encode())));
+
+    // must fail since the token 2 nested into the token 1 is soulbound
+    let res = nft.send(
+        USERS[0],
+        Action::SetTokenSoulbound {
+            token_id: 2_i32.into(),
+            soulbound: true,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 1_i32.into(),
+            parent: (2.into(), 0_i32.into()),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::SetTokenSoulbound {
+            token_id: 2_i32.into(),
+            soulbound: false,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // the programs nested into each other make a loop that the root owner query stops
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 1_i32.into(),
+            parent: (2.into(), 0_i32.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = items.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 0_i32.into(),
+            parent: (1.into(), 2_i32.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    // must fail since the root owner can't be resolved
+    let res = nft.send(USERS[1], Action::RootOwner(2_i32.into()));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn lock() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the caller is neither the owner nor an approved actor
+    let res = nft.send(
+        USERS[1],
+        Action::Lock {
+            token_id: 0_i32.into(),
+            locker: USERS[1].into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // the approved actor locks the token for itself
+    let res = nft.send(
+        USERS[0],
+        Action::Approve {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Lock {
+            token_id: 0_i32.into(),
+            locker: USERS[1].into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Locked {
+            token_id: 0_i32.into(),
+            locker: USERS[1].into(),
+        }
+        .encode()
+    )));
+
+    // must fail since the token is locked
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Approve {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(USERS[0], Action::Burn(0_i32.into()));
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Lock {
+            token_id: 0_i32.into(),
+            locker: USERS[0].into(),
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since only the locker can unlock the token
+    let res = nft.send(USERS[0], Action::Unlock(0_i32.into()));
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[1], Action::Unlock(0_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::Unlocked(0_i32.into()).encode())));
+    // must fail since the token is not locked anymore
+    let res = nft.send(USERS[1], Action::Unlock(0_i32.into()));
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn lock_nested() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    for _ in 0..2 {
+        let res = nft.send(USERS[0], Action::Mint);
+        assert!(!res.main_failed());
+    }
+    // the token 2 is nested into the token 1 and locked there
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 2_i32.into(),
+            parent: (1.into(), 1_i32.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Lock {
+            token_id: 2_i32.into(),
+            locker: USERS[1].into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    // must fail since moving the token 1 would move the locked token
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 1_i32.into(),
+            parent: (1.into(), 0_i32.into()),
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send(USERS[1], Action::Unlock(2_i32.into()));
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 1_i32.into(),
+            parent: (1.into(), 0_i32.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Lock {
+            token_id: 2_i32.into(),
+            locker: USERS[1].into(),
+        },
+    );
+    assert!(!res.main_failed());
+    // must fail since detaching the token 1 would move the locked token
+    let res = nft.send(
+        USERS[0],
+        Action::Detach {
+            token_id: 1_i32.into(),
+            to: USERS[2].into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = nft.send(USERS[0], Action::OwnerOf(2_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[0].into()).encode())));
+}
+
+#[test]
+fn expiry() {
+    let sys = System::new();
+    sys.init_logger();
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        InitConfig {
+            name: String::from("MyMembership"),
+            symbol: String::from("MMB"),
+            max_royalty_rate: 1_000,
+            expiry: Some(ExpiryConfig {
+                period: 10_000,
+                extension_price: Some(100),
+                transferable_when_expired: false,
+            }),
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    // the token expires after the period
+    sys.spend_blocks(20);
+    // must fail since the token has expired
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the caller isn't the token owner
+    let res = nft.send_with_value(
+        USERS[0],
+        Action::Extend {
+            token_id: 0_i32.into(),
+            periods: 1,
+        },
+        100,
+    );
+    assert!(res.main_failed());
+    // must fail since the attached value is less than the extension price
+    let res = nft.send_with_value(
+        USERS[1],
+        Action::Extend {
+            token_id: 0_i32.into(),
+            periods: 2,
+        },
+        199,
+    );
+    assert!(res.main_failed());
+
+    let res = nft.send_with_value(
+        USERS[1],
+        Action::Extend {
+            token_id: 0_i32.into(),
+            periods: 2,
+        },
+        200,
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = nft.send(USERS[0], Action::Withdraw);
     assert!(res.contains(&(
         USERS[0],
-        Event::Royalty {
-            amount: 0.into(),
-            origin: USERS[0].into(),
+        Event::Withdrawn {
+            to: USERS[0].into(),
+            amount: 200,
         }
         .encode()
     )));
 }
 
-#[test]
-fn royalty_failures() {
-    let sys = System::new();
-    init_with_mint(&sys);
-    let nft = sys.get_program(1);
-    let res = nft.send(USERS[0], Action::Royalty {token_id: 1.into(), price: 0});
-    assert!(res.main_failed());
-    let res = nft.send(USERS[1], Action::Royalty {token_id: 0.into(), price: 0});
-    assert!(res.main_failed());
+fn token_history(
+    nft: &Program,
+    token_id: u64,
+    offset: u32,
+    limit: u32,
+) -> Vec<(ActorId, ActorId, Option<u128>)> {
+    let res = nft.send(
+        USERS[2],
+        Action::TokenHistory {
+            token_id: token_id.into(),
+            offset,
+            limit,
+        },
+    );
+    res.log()
+        .iter()
+        .find_map(|log| match Event::decode(&mut log.payload()) {
+            Ok(Event::TokenHistory(history)) => Some(history),
+            _ => None,
+        })
+        .expect("No token history in the reply")
+        .iter()
+        .map(|record| (record.from, record.to, record.price))
+        .collect()
 }
 
-fn assignroyalty() {
+#[test]
+fn ownership_history() {
     let sys = System::new();
     init_with_mint(&sys);
     let nft = sys.get_program(1);
-    let res = nft.send(USERS[0], Action::AssignRoyalty {token_id: 0.into(), rate: 0});
-    assert!(res.contains(&(
+    let receiver = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_receiver.wasm",
+    );
+    let res = receiver.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+    let zero: ActorId = 0.into();
+    let (user0, user1, user2): (ActorId, ActorId, ActorId) =
+        (USERS[0].into(), USERS[1].into(), USERS[2].into());
+
+    let res = nft.send(
         USERS[0],
-        Event::AssignRoyalty {
+        Action::Transfer {
+            to: USERS[1].into(),
             token_id: 0_i32.into(),
-            recipient: USERS[0].into(),
-        }
-        .encode()
-    )));
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::Approve {
+            to: USERS[2].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send_with_value(
+        USERS[2],
+        Action::Sale {
+            token_id: 0_i32.into(),
+            to: USERS[2].into(),
+        },
+        1_000,
+    );
+    assert!(!res.main_failed());
+    assert_eq!(
+        token_history(&nft, 0, 0, 10),
+        vec![
+            (zero, user0, None),
+            (user0, user1, None),
+            (user1, user2, Some(1_000)),
+        ]
+    );
+    assert_eq!(token_history(&nft, 0, 1, 1), vec![(user0, user1, None)]);
+    assert!(token_history(&nft, 0, 5, 10).is_empty());
+
+    // the history is kept after the burn
+    let res = nft.send(USERS[2], Action::Burn(0_i32.into()));
+    assert!(!res.main_failed());
+    assert_eq!(token_history(&nft, 0, 3, 10), vec![(user2, zero, None)]);
+
+    // only the latest changes are kept
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+    for i in 0..MAX_HISTORY_LEN {
+        let (from, to) = if i % 2 == 0 {
+            (USERS[0], USERS[1])
+        } else {
+            (USERS[1], USERS[0])
+        };
+        let res = nft.send(
+            from,
+            Action::Transfer {
+                to: to.into(),
+                token_id: 1_i32.into(),
+            },
+        );
+        assert!(!res.main_failed());
+    }
+    let history = token_history(&nft, 1, 0, u32::MAX);
+    assert_eq!(history.len(), MAX_HISTORY_LEN);
+    assert_eq!(history[0], (user0, user1, None));
+
+    // the transfer rejected by the receiver leaves no trace in the history
+    let res = nft.send(
+        USERS[0],
+        Action::SafeTransfer {
+            to: 2.into(),
+            token_id: 1_i32.into(),
+            data: b"reject".to_vec(),
+            to_program: true,
+        },
+    );
+    assert!(!res.main_failed());
+    assert_eq!(token_history(&nft, 1, 0, u32::MAX), history);
 }
 
 #[test]
@@ -211,6 +1922,235 @@ fn transfer_failures() {
     assert!(res.main_failed());
 }
 
+#[test]
+fn safe_transfer_failures() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    // must fail since the token doesn't exist
+    let res = nft.send(
+        USERS[0],
+        Action::SafeTransfer {
+            to: USERS[1].into(),
+            token_id: 100_i32.into(),
+            data: vec![],
+            to_program: false,
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the caller isn't an authorized source
+    let res = nft.send(
+        USERS[2],
+        Action::SafeTransfer {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+            data: vec![],
+            to_program: false,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn safe_transfer() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let receiver = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_receiver.wasm",
+    );
+    let res = receiver.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+
+    let res = nft.send(
+        USERS[0],
+        Action::SafeTransfer {
+            to: 2.into(),
+            token_id: 0_i32.into(),
+            data: vec![],
+            to_program: true,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Transfer {
+            from: USERS[0].into(),
+            to: 2.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[0], Action::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(2.into()).encode())));
+
+    // the receiver rejects the token, so the ownership is reverted
+    let res = nft.send(
+        USERS[0],
+        Action::Approve {
+            to: USERS[1].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[1],
+        Action::SafeTransfer {
+            to: 2.into(),
+            token_id: 1_i32.into(),
+            data: b"reject".to_vec(),
+            to_program: true,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::TransferRejected {
+            from: USERS[1].into(),
+            to: 2.into(),
+            token_id: 1_i32.into(),
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[0], Action::OwnerOf(1_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[0].into()).encode())));
+    let res = nft.send(USERS[0], Action::BalanceOf(USERS[0].into()));
+    assert!(res.contains(&(USERS[0], Event::BalanceOf(1_i32.into()).encode())));
+    let res = nft.send(USERS[0], Action::BalanceOf(2.into()));
+    assert!(res.contains(&(USERS[0], Event::BalanceOf(1_i32.into()).encode())));
+    // the approval is restored together with the ownership
+    let res = nft.send(
+        USERS[1],
+        Action::Transfer {
+            to: USERS[2].into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    // an account isn't notified, the token is transferred right away
+    let res = nft.send(
+        USERS[2],
+        Action::SafeTransfer {
+            to: USERS[0].into(),
+            token_id: 1_i32.into(),
+            data: vec![],
+            to_program: false,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[2],
+        Event::Transfer {
+            from: USERS[2].into(),
+            to: USERS[0].into(),
+            token_id: 1_i32.into(),
+        }
+        .encode()
+    )));
+}
+
+// the receiver sends the action to the NFT contract while the transfer waits for its reply, then rejects the token
+fn call_and_reject(action: Action) -> Vec<u8> {
+    let mut data = b"call-and-reject:".to_vec();
+    data.extend(action.encode());
+    data
+}
+
+#[test]
+fn safe_transfer_pending() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let receiver = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_receiver.wasm",
+    );
+    let res = receiver.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], Action::Mint);
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        Action::Transfer {
+            to: 2.into(),
+            token_id: 1_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    // the receiver can't nest the token into its own one before rejecting it
+    let res = nft.send(
+        USERS[0],
+        Action::SafeTransfer {
+            to: 2.into(),
+            token_id: 0_i32.into(),
+            data: call_and_reject(Action::Nest {
+                token_id: 0_i32.into(),
+                parent: (1.into(), 1_i32.into()),
+            }),
+            to_program: true,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferRejected {
+            from: USERS[0].into(),
+            to: 2.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    let res = nft.send(USERS[0], Action::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OwnerOf(USERS[0].into()).encode())));
+    // must fail since the token isn't nested
+    let res = nft.send(
+        2,
+        Action::Detach {
+            token_id: 0_i32.into(),
+            to: 2.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // the receiver can't lock the token before rejecting it, so it comes back unlocked
+    let res = nft.send(
+        USERS[0],
+        Action::SafeTransfer {
+            to: 2.into(),
+            token_id: 0_i32.into(),
+            data: call_and_reject(Action::Lock {
+                token_id: 0_i32.into(),
+                locker: 2.into(),
+            }),
+            to_program: true,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::TransferRejected {
+            from: USERS[0].into(),
+            to: 2.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    // must fail since the token isn't locked
+    let res = nft.send(2, Action::Unlock(0_i32.into()));
+    assert!(res.main_failed());
+
+    // must fail since the token is nested into the zero address
+    let res = nft.send(
+        USERS[0],
+        Action::Nest {
+            token_id: 0_i32.into(),
+            parent: (0.into(), 1_i32.into()),
+        },
+    );
+    assert!(res.main_failed());
+}
+
 #[test]
 fn approve_and_transfer() {
     let sys = System::new();
@@ -324,4 +2264,29 @@ fn approve_for_all() {
         },
     );
     assert!(res.main_failed());
-}
\ No newline at end of file
+}
+
+#[test]
+fn approve_for_all_non_operator() {
+    let sys = System::new();
+    init_with_mint(&sys);
+    let nft = sys.get_program(1);
+    let res = nft.send(
+        USERS[0],
+        Action::ApproveForAll {
+            to: USERS[1].into(),
+            approved: true,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // must fail since only `USERS[1]` is the operator of `USERS[0]`
+    let res = nft.send(
+        USERS[2],
+        Action::Transfer {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+}
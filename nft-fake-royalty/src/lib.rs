@@ -0,0 +1,51 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use gstd::{msg, prelude::*, ActorId};
+use nft_example_io::{Action, Event};
+use primitive_types::U256;
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+
+static mut OWNERS: BTreeMap<U256, ActorId> = BTreeMap::new();
+static mut ROYALTY_RECEIVER: ActorId = ZERO_ID;
+
+/// An NFT contract that reports the royalty twice as large as the sale price,
+/// it lets programs that trust the royalty of the NFT contract be tested against a broken one
+/// Anyone can transfer any token, `Mint` gives the caller the next token ID
+#[no_mangle]
+pub unsafe extern "C" fn handle() {
+    let action: Action = msg::load().expect("Could not load Action");
+    let reply = match action {
+        Action::Mint => {
+            let token_id = U256::from(OWNERS.len());
+            OWNERS.insert(token_id, msg::source());
+            Event::Transfer {
+                from: ZERO_ID,
+                to: msg::source(),
+                token_id,
+            }
+        }
+        Action::Transfer { to, token_id } => {
+            let from = OWNERS
+                .insert(token_id, to)
+                .expect("NonFungibleToken: token does not exist");
+            Event::Transfer { from, to, token_id }
+        }
+        Action::OwnerOf(token_id) => Event::OwnerOf(*OWNERS.get(&token_id).unwrap_or(&ZERO_ID)),
+        Action::Royalty { price, .. } => {
+            let amount = price.saturating_mul(2);
+            Event::Royalty {
+                amount,
+                recipients: vec![(ROYALTY_RECEIVER, amount)],
+            }
+        }
+        _ => panic!("NonFungibleToken: action is not supported"),
+    };
+    msg::reply(reply, 0, 0);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn init() {
+    ROYALTY_RECEIVER = msg::source();
+}
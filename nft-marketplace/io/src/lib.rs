@@ -0,0 +1,106 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct Listing {
+    pub seller: ActorId,
+    pub price: u128,
+    /// The fungible token the price is paid in, or `None` for the native value
+    pub ft_contract: Option<ActorId>,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Action {
+    List {
+        nft_contract: ActorId,
+        token_id: U256,
+        price: u128,
+        ft_contract: Option<ActorId>,
+    },
+    UpdatePrice {
+        nft_contract: ActorId,
+        token_id: U256,
+        price: u128,
+    },
+    Delist {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Buy {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    /// Pays the caller the fungible tokens of the payouts that failed
+    Claim(ActorId),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    Listed {
+        nft_contract: ActorId,
+        token_id: U256,
+        seller: ActorId,
+        price: u128,
+        ft_contract: Option<ActorId>,
+    },
+    PriceUpdated {
+        nft_contract: ActorId,
+        token_id: U256,
+        price: u128,
+    },
+    Delisted {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Sold {
+        nft_contract: ActorId,
+        token_id: U256,
+        seller: ActorId,
+        buyer: ActorId,
+        price: u128,
+        royalties: Vec<(ActorId, u128)>,
+    },
+    /// The payment is returned to the buyer and the token is listed again
+    PurchaseFailed {
+        nft_contract: ActorId,
+        token_id: U256,
+        buyer: ActorId,
+    },
+    Claimed {
+        ft_contract: ActorId,
+        to: ActorId,
+        amount: u128,
+    },
+    /// The NFT contract refused to return the token, it stays listed
+    DelistFailed {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    /// The fungible token contract refused the payout, it's kept to be claimed again
+    ClaimFailed { ft_contract: ActorId, to: ActorId },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum State {
+    Listing {
+        nft_contract: ActorId,
+        token_id: U256,
+    },
+    Listings,
+    /// The fungible tokens kept for the account after the failed payouts
+    Unclaimed {
+        ft_contract: ActorId,
+        account: ActorId,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Listing(Option<Listing>),
+    Listings(Vec<(ActorId, U256, Listing)>),
+    Unclaimed(u128),
+}
@@ -0,0 +1,404 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use fungible_token_messages::{
+    Action as FTAction, Event as FTEvent, TransferFromInput, TransferInput,
+};
+use gstd::{debug, exec, msg, prelude::*, ActorId};
+use nft_example_client::{nft_owner, nft_royalty, nft_transfer};
+use nft_marketplace_io::{Action, Event, Listing, State, StateReply};
+use primitive_types::U256;
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+const GAS_FOR_FT: u64 = 500_000_000;
+
+#[derive(Debug)]
+pub struct Marketplace {
+    /// Listed tokens by the NFT contract and the token ID, the tokens are held by the marketplace
+    pub listings: BTreeMap<(ActorId, U256), Listing>,
+    /// The fungible token payouts that failed, by the fungible token contract and the recipient
+    pub unclaimed: BTreeMap<(ActorId, ActorId), u128>,
+}
+
+static mut MARKETPLACE: Marketplace = Marketplace {
+    listings: BTreeMap::new(),
+    unclaimed: BTreeMap::new(),
+};
+
+/// Returns false if the fungible token contract refused the transfer
+async fn ft_transfer_from(
+    ft_contract: &ActorId,
+    owner: &ActorId,
+    to: &ActorId,
+    amount: u128,
+) -> bool {
+    msg::send_and_wait_for_reply::<FTEvent, _>(
+        *ft_contract,
+        FTAction::TransferFrom(TransferFromInput {
+            owner: *owner,
+            to: *to,
+            amount,
+        }),
+        GAS_FOR_FT,
+        0,
+    )
+    .await
+    .is_ok()
+}
+
+/// Pays `amount` from the marketplace either in the fungible token or in the native value
+/// Returns false if the fungible token contract refused the transfer
+async fn pay(ft_contract: &Option<ActorId>, to: &ActorId, amount: u128) -> bool {
+    if amount == 0 || to == &ZERO_ID {
+        return true;
+    }
+    match ft_contract {
+        Some(ft_contract) => msg::send_and_wait_for_reply::<FTEvent, _>(
+            *ft_contract,
+            FTAction::Transfer(TransferInput { to: *to, amount }),
+            GAS_FOR_FT,
+            0,
+        )
+        .await
+        .is_ok(),
+        None => {
+            msg::send_bytes(*to, b"", 0, amount);
+            true
+        }
+    }
+}
+
+impl Marketplace {
+    /// Takes the token from the seller, who must have approved the marketplace, and lists it
+    async fn list(
+        &mut self,
+        nft_contract: ActorId,
+        token_id: U256,
+        price: u128,
+        ft_contract: Option<ActorId>,
+    ) {
+        let seller = msg::source();
+        if price == 0 {
+            panic!("Marketplace: Price must be greater than zero");
+        }
+        if self.listings.contains_key(&(nft_contract, token_id)) {
+            panic!("Marketplace: Token is already listed");
+        }
+        if nft_owner(&nft_contract, token_id).await != Some(seller) {
+            panic!("Marketplace: Only the token owner can list it");
+        }
+        if !nft_transfer(&nft_contract, &exec::program_id(), token_id).await {
+            panic!("Marketplace: Unable to take the token from the seller");
+        }
+        self.listings.insert(
+            (nft_contract, token_id),
+            Listing {
+                seller,
+                price,
+                ft_contract,
+            },
+        );
+        msg::reply(
+            Event::Listed {
+                nft_contract,
+                token_id,
+                seller,
+                price,
+                ft_contract,
+            },
+            0,
+            0,
+        );
+    }
+
+    fn update_price(&mut self, nft_contract: ActorId, token_id: U256, price: u128) {
+        if price == 0 {
+            panic!("Marketplace: Price must be greater than zero");
+        }
+        let listing = self
+            .listings
+            .get_mut(&(nft_contract, token_id))
+            .expect("Marketplace: Token is not listed");
+        if listing.seller != msg::source() {
+            panic!("Marketplace: Only the seller can update the price");
+        }
+        listing.price = price;
+        msg::reply(
+            Event::PriceUpdated {
+                nft_contract,
+                token_id,
+                price,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Removes the listing and returns the token to the seller
+    /// The token stays listed if the NFT contract refuses to return it
+    async fn delist(&mut self, nft_contract: ActorId, token_id: U256) {
+        let listing = self
+            .listings
+            .remove(&(nft_contract, token_id))
+            .expect("Marketplace: Token is not listed");
+        if listing.seller != msg::source() {
+            panic!("Marketplace: Only the seller can delist the token");
+        }
+        if !nft_transfer(&nft_contract, &listing.seller, token_id).await {
+            debug!("Marketplace: Unable to return {:?} to the seller", token_id);
+            self.listings.insert((nft_contract, token_id), listing);
+            msg::reply(
+                Event::DelistFailed {
+                    nft_contract,
+                    token_id,
+                },
+                0,
+                0,
+            );
+            return;
+        }
+        msg::reply(
+            Event::Delisted {
+                nft_contract,
+                token_id,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Sells the token to the caller
+    /// The price is paid with the attached value or taken from the caller's fungible token allowance,
+    /// then the royalty recipients reported by the NFT contract are paid and the rest goes to the seller
+    /// The purchase fails and the payment is returned if the reported royalty exceeds the price
+    async fn buy(&mut self, nft_contract: ActorId, token_id: U256) {
+        let buyer = msg::source();
+        // the listing stays out of the map until the purchase is done or failed, so nobody else can buy it meanwhile
+        let listing = self
+            .listings
+            .remove(&(nft_contract, token_id))
+            .expect("Marketplace: Token is not listed");
+        if listing.seller == buyer {
+            panic!("Marketplace: Seller can not buy the own token");
+        }
+        match listing.ft_contract {
+            None => {
+                if msg::value() != listing.price {
+                    panic!("Marketplace: Attached value must be equal to the price");
+                }
+            }
+            Some(ft_contract) => {
+                if msg::value() != 0 {
+                    panic!("Marketplace: Token is sold for the fungible token");
+                }
+                if !ft_transfer_from(&ft_contract, &buyer, &exec::program_id(), listing.price).await
+                {
+                    debug!("Marketplace: Unable to take the payment from {:?}", buyer);
+                    self.listings.insert((nft_contract, token_id), listing);
+                    msg::reply(
+                        Event::PurchaseFailed {
+                            nft_contract,
+                            token_id,
+                            buyer,
+                        },
+                        0,
+                        0,
+                    );
+                    return;
+                }
+            }
+        }
+
+        // the payment is returned and the token is listed again if the sale can't be completed
+        // the NFT contract can be any program, so the royalty it reports must fit into the price
+        let royalties = nft_royalty(&nft_contract, token_id, listing.price).await;
+        let paid = royalties.as_ref().and_then(|royalties| {
+            royalties
+                .iter()
+                .try_fold(0u128, |paid, (_, amount)| paid.checked_add(*amount))
+        });
+        let (royalties, paid) = match (royalties, paid) {
+            (Some(royalties), Some(paid)) if paid <= listing.price => (royalties, paid),
+            _ => {
+                debug!("Marketplace: Invalid royalty of {:?}", token_id);
+                self.fail_purchase(nft_contract, token_id, listing, buyer)
+                    .await;
+                return;
+            }
+        };
+        if !nft_transfer(&nft_contract, &buyer, token_id).await {
+            debug!(
+                "Marketplace: Unable to transfer {:?} to {:?}",
+                token_id, buyer
+            );
+            self.fail_purchase(nft_contract, token_id, listing, buyer)
+                .await;
+            return;
+        }
+        for (recipient, amount) in &royalties {
+            self.pay_or_keep(&listing.ft_contract, recipient, *amount)
+                .await;
+        }
+        self.pay_or_keep(&listing.ft_contract, &listing.seller, listing.price - paid)
+            .await;
+        msg::reply(
+            Event::Sold {
+                nft_contract,
+                token_id,
+                seller: listing.seller,
+                buyer,
+                price: listing.price,
+                royalties,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Returns the payment to the buyer and lists the token again
+    async fn fail_purchase(
+        &mut self,
+        nft_contract: ActorId,
+        token_id: U256,
+        listing: Listing,
+        buyer: ActorId,
+    ) {
+        self.pay_or_keep(&listing.ft_contract, &buyer, listing.price)
+            .await;
+        self.listings.insert((nft_contract, token_id), listing);
+        msg::reply(
+            Event::PurchaseFailed {
+                nft_contract,
+                token_id,
+                buyer,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Pays `amount` to `to`, a failed fungible token payout is kept for `to` to claim later
+    async fn pay_or_keep(&mut self, ft_contract: &Option<ActorId>, to: &ActorId, amount: u128) {
+        if !pay(ft_contract, to, amount).await {
+            if let Some(ft_contract) = ft_contract {
+                let unclaimed = self.unclaimed.entry((*ft_contract, *to)).or_default();
+                *unclaimed = unclaimed.saturating_add(amount);
+            }
+        }
+    }
+
+    /// Pays the caller the fungible tokens kept for it after the failed payouts
+    /// The payouts are kept again if the fungible token contract refuses them
+    async fn claim(&mut self, ft_contract: ActorId) {
+        let to = msg::source();
+        let amount = self
+            .unclaimed
+            .remove(&(ft_contract, to))
+            .expect("Marketplace: Nothing to claim");
+        if !pay(&Some(ft_contract), &to, amount).await {
+            debug!("Marketplace: Unable to pay {:?} to {:?}", amount, to);
+            self.unclaimed.insert((ft_contract, to), amount);
+            msg::reply(Event::ClaimFailed { ft_contract, to }, 0, 0);
+            return;
+        }
+        msg::reply(
+            Event::Claimed {
+                ft_contract,
+                to,
+                amount,
+            },
+            0,
+            0,
+        );
+    }
+}
+
+gstd::metadata! {
+    title: "NFT Marketplace",
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: Action = msg::load().expect("Could not load Action");
+    let marketplace = unsafe { &mut MARKETPLACE };
+    match action {
+        Action::List {
+            nft_contract,
+            token_id,
+            price,
+            ft_contract,
+        } => {
+            marketplace
+                .list(nft_contract, token_id, price, ft_contract)
+                .await;
+        }
+        Action::UpdatePrice {
+            nft_contract,
+            token_id,
+            price,
+        } => {
+            marketplace.update_price(nft_contract, token_id, price);
+        }
+        Action::Delist {
+            nft_contract,
+            token_id,
+        } => {
+            marketplace.delist(nft_contract, token_id).await;
+        }
+        Action::Buy {
+            nft_contract,
+            token_id,
+        } => {
+            marketplace.buy(nft_contract, token_id).await;
+        }
+        Action::Claim(ft_contract) => {
+            marketplace.claim(ft_contract).await;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::Listing {
+            nft_contract,
+            token_id,
+        } => {
+            let listing = MARKETPLACE.listings.get(&(nft_contract, token_id)).cloned();
+            StateReply::Listing(listing).encode()
+        }
+        State::Listings => {
+            let listings = MARKETPLACE
+                .listings
+                .iter()
+                .map(|((nft_contract, token_id), listing)| {
+                    (*nft_contract, *token_id, listing.clone())
+                })
+                .collect();
+            StateReply::Listings(listings).encode()
+        }
+        State::Unclaimed {
+            ft_contract,
+            account,
+        } => {
+            let amount = *MARKETPLACE
+                .unclaimed
+                .get(&(ft_contract, account))
+                .unwrap_or(&0);
+            StateReply::Unclaimed(amount).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
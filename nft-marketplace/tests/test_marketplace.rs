@@ -0,0 +1,447 @@
+use codec::Encode;
+use fungible_token_messages::{
+    Action as FTAction, ApproveInput, Event as FTEvent, InitConfig as FTInitConfig, MintInput,
+};
+use gtest::{Program, System};
+use nft_example_io::{
+    Action as NFTAction, Event as NFTEvent, ExpiryConfig, InitConfig as NFTInitConfig,
+};
+use nft_marketplace_io::*;
+
+const USERS: &'static [u64] = &[4, 5, 6];
+const NFT_ID: u64 = 1;
+const FT_ID: u64 = 2;
+const MARKETPLACE_ID: u64 = 3;
+const FAKE_NFT_ID: u64 = 4;
+
+fn init(sys: &System) {
+    init_with_expiry(sys, None);
+}
+
+fn init_with_expiry(sys: &System, expiry: Option<ExpiryConfig>) {
+    sys.init_logger();
+
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        NFTInitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            expiry,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    // the minter of the token receives the default royalty of 5%
+    let res = nft.send(USERS[0], NFTAction::Mint);
+    assert!(!res.main_failed());
+    let res = nft.send(
+        USERS[0],
+        NFTAction::Transfer {
+            to: USERS[1].into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let ft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/fungible_token.wasm",
+    );
+    let res = ft.send(
+        USERS[0],
+        FTInitConfig {
+            name: String::from("MyFungibleToken"),
+            symbol: String::from("MFT"),
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = ft.send(
+        USERS[0],
+        FTAction::Mint(MintInput {
+            account: USERS[2].into(),
+            amount: 1_000,
+        }),
+    );
+    assert!(!res.main_failed());
+
+    let marketplace = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_marketplace.wasm",
+    );
+    let res = marketplace.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+}
+
+fn list(sys: &System, price: u128, ft_contract: Option<u64>) {
+    let nft = sys.get_program(NFT_ID);
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+    let res = nft.send(
+        USERS[1],
+        NFTAction::Approve {
+            to: MARKETPLACE_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = marketplace.send(
+        USERS[1],
+        Action::List {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            price,
+            ft_contract: ft_contract.map(|id| id.into()),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Listed {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            seller: USERS[1].into(),
+            price,
+            ft_contract: ft_contract.map(|id| id.into()),
+        }
+        .encode()
+    )));
+}
+
+fn assert_owner(sys: &System, owner: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(USERS[0], NFTAction::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], NFTEvent::OwnerOf(owner.into()).encode())));
+}
+
+fn assert_ft_balance(sys: &System, account: u64, balance: u128) {
+    let ft = sys.get_program(FT_ID);
+    let res = ft.send(USERS[0], FTAction::BalanceOf(account.into()));
+    assert!(res.contains(&(USERS[0], FTEvent::Balance(balance).encode())));
+}
+
+#[test]
+fn list_failures() {
+    let sys = System::new();
+    init(&sys);
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+    let action = |price| Action::List {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+        price,
+        ft_contract: None,
+    };
+    // must fail since the marketplace isn't approved
+    let res = marketplace.send(USERS[1], action(1_000));
+    assert!(res.main_failed());
+    // must fail since the caller isn't the token owner
+    let res = marketplace.send(USERS[2], action(1_000));
+    assert!(res.main_failed());
+    // must fail since the price is zero
+    let res = marketplace.send(USERS[1], action(0));
+    assert!(res.main_failed());
+
+    list(&sys, 1_000, None);
+    assert_owner(&sys, MARKETPLACE_ID);
+    // must fail since the token is already listed
+    let res = marketplace.send(USERS[1], action(1_000));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn buy_with_value() {
+    let sys = System::new();
+    init(&sys);
+    list(&sys, 1_000, None);
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+    let action = || Action::Buy {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+    };
+    // must fail since the attached value isn't equal to the price
+    let res = marketplace.send_with_value(USERS[2], action(), 999);
+    assert!(res.main_failed());
+    // must fail since the seller can't buy the own token
+    let res = marketplace.send_with_value(USERS[1], action(), 1_000);
+    assert!(res.main_failed());
+
+    let res = marketplace.send_with_value(USERS[2], action(), 1_000);
+    assert!(res.contains(&(
+        USERS[2],
+        Event::Sold {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            seller: USERS[1].into(),
+            buyer: USERS[2].into(),
+            price: 1_000,
+            royalties: vec![(USERS[0].into(), 50)],
+        }
+        .encode()
+    )));
+    // the royalty receiver and the seller are paid
+    assert!(res.contains(&(USERS[0], Vec::<u8>::new())));
+    assert!(res.contains(&(USERS[1], Vec::<u8>::new())));
+    assert_owner(&sys, USERS[2]);
+
+    // must fail since the token isn't listed anymore
+    let res = marketplace.send_with_value(USERS[0], action(), 1_000);
+    assert!(res.main_failed());
+}
+
+#[test]
+fn buy_expired_token() {
+    let sys = System::new();
+    init_with_expiry(
+        &sys,
+        Some(ExpiryConfig {
+            period: 30_000,
+            extension_price: None,
+            transferable_when_expired: false,
+        }),
+    );
+    list(&sys, 1_000, Some(FT_ID));
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+    let ft = sys.get_program(FT_ID);
+    let res = ft.send(
+        USERS[2],
+        FTAction::Approve(ApproveInput {
+            spender: MARKETPLACE_ID.into(),
+            amount: 1_000,
+        }),
+    );
+    assert!(!res.main_failed());
+
+    // the NFT contract refuses to transfer the expired token,
+    // so the payment is returned and the token stays listed
+    sys.spend_blocks(100);
+    let res = marketplace.send(
+        USERS[2],
+        Action::Buy {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[2],
+        Event::PurchaseFailed {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            buyer: USERS[2].into(),
+        }
+        .encode()
+    )));
+    assert_owner(&sys, MARKETPLACE_ID);
+    assert_ft_balance(&sys, USERS[2], 1_000);
+    assert_ft_balance(&sys, MARKETPLACE_ID, 0);
+
+    // the token can't be returned either, so it stays listed for the seller
+    let res = marketplace.send(
+        USERS[1],
+        Action::Delist {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::DelistFailed {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    assert_owner(&sys, MARKETPLACE_ID);
+    let res = marketplace.send(
+        USERS[1],
+        Action::UpdatePrice {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            price: 2_000,
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn buy_with_excessive_royalty() {
+    let sys = System::new();
+    init(&sys);
+    let fake_nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_fake_royalty.wasm",
+    );
+    let res = fake_nft.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+    let res = fake_nft.send(USERS[1], NFTAction::Mint);
+    assert!(!res.main_failed());
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+    let res = marketplace.send(
+        USERS[1],
+        Action::List {
+            nft_contract: FAKE_NFT_ID.into(),
+            token_id: 0_i32.into(),
+            price: 1_000,
+            ft_contract: Some(FT_ID.into()),
+        },
+    );
+    assert!(!res.main_failed());
+    let ft = sys.get_program(FT_ID);
+    let res = ft.send(
+        USERS[2],
+        FTAction::Approve(ApproveInput {
+            spender: MARKETPLACE_ID.into(),
+            amount: 1_000,
+        }),
+    );
+    assert!(!res.main_failed());
+
+    // the fake NFT contract reports the royalty twice as large as the price,
+    // so the payment is returned and the token stays listed
+    let res = marketplace.send(
+        USERS[2],
+        Action::Buy {
+            nft_contract: FAKE_NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[2],
+        Event::PurchaseFailed {
+            nft_contract: FAKE_NFT_ID.into(),
+            token_id: 0_i32.into(),
+            buyer: USERS[2].into(),
+        }
+        .encode()
+    )));
+    let res = fake_nft.send(USERS[0], NFTAction::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[0], NFTEvent::OwnerOf(MARKETPLACE_ID.into()).encode())));
+    assert_ft_balance(&sys, USERS[2], 1_000);
+    assert_ft_balance(&sys, USERS[0], 0);
+    assert_ft_balance(&sys, MARKETPLACE_ID, 0);
+}
+
+#[test]
+fn buy_with_fungible_token() {
+    let sys = System::new();
+    init(&sys);
+    list(&sys, 1_000, Some(FT_ID));
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+    let ft = sys.get_program(FT_ID);
+    let action = || Action::Buy {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+    };
+
+    // the payment fails since the buyer hasn't approved the marketplace
+    let res = marketplace.send(USERS[2], action());
+    assert!(res.contains(&(
+        USERS[2],
+        Event::PurchaseFailed {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            buyer: USERS[2].into(),
+        }
+        .encode()
+    )));
+    assert_owner(&sys, MARKETPLACE_ID);
+
+    let res = ft.send(
+        USERS[2],
+        FTAction::Approve(ApproveInput {
+            spender: MARKETPLACE_ID.into(),
+            amount: 1_000,
+        }),
+    );
+    assert!(!res.main_failed());
+    let res = marketplace.send(USERS[2], action());
+    assert!(res.contains(&(
+        USERS[2],
+        Event::Sold {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            seller: USERS[1].into(),
+            buyer: USERS[2].into(),
+            price: 1_000,
+            royalties: vec![(USERS[0].into(), 50)],
+        }
+        .encode()
+    )));
+    assert_owner(&sys, USERS[2]);
+    assert_ft_balance(&sys, USERS[0], 50);
+    assert_ft_balance(&sys, USERS[1], 950);
+    assert_ft_balance(&sys, USERS[2], 0);
+}
+
+#[test]
+fn update_price_and_delist() {
+    let sys = System::new();
+    init(&sys);
+    list(&sys, 1_000, None);
+    let marketplace = sys.get_program(MARKETPLACE_ID);
+
+    // must fail since the caller isn't the seller
+    let res = marketplace.send(
+        USERS[2],
+        Action::UpdatePrice {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            price: 1,
+        },
+    );
+    assert!(res.main_failed());
+    let res = marketplace.send(
+        USERS[1],
+        Action::UpdatePrice {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            price: 2_000,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::PriceUpdated {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+            price: 2_000,
+        }
+        .encode()
+    )));
+    // must fail since the price has been raised
+    let res = marketplace.send_with_value(
+        USERS[2],
+        Action::Buy {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+        1_000,
+    );
+    assert!(res.main_failed());
+
+    // must fail since the caller isn't the seller
+    let res = marketplace.send(
+        USERS[2],
+        Action::Delist {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = marketplace.send(
+        USERS[1],
+        Action::Delist {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Delisted {
+            nft_contract: NFT_ID.into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    assert_owner(&sys, USERS[1]);
+}
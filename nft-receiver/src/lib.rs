@@ -0,0 +1,41 @@
+#![no_std]
+
+use gstd::{debug, exec, msg};
+use non_fungible_token::receiver::{NftReceiverAction, NftReceiverReply};
+
+/// The data that makes the receiver refuse the token
+const REJECT: &[u8] = b"reject";
+/// The data prefix that makes the receiver send the rest of the data to the NFT contract
+/// and refuse the token after the reply, so the token can be acted on while its transfer is pending
+const CALL_AND_REJECT: &[u8] = b"call-and-reject:";
+
+/// An example of a program that accepts NFTs sent with `SafeTransfer`
+#[gstd::async_main]
+async fn main() {
+    let action: NftReceiverAction = msg::load().expect("Could not load NftReceiverAction");
+    let reply = match action {
+        NftReceiverAction::OnNftReceived { token_id, data, .. } => {
+            debug!("NFT receiver got the token {:?}", token_id);
+            match data.as_slice() {
+                REJECT => NftReceiverReply::Rejected,
+                data if data.starts_with(CALL_AND_REJECT) => {
+                    // half of the gas is kept to reply after the call
+                    let reply = msg::send_bytes_and_wait_for_reply(
+                        msg::source(),
+                        &data[CALL_AND_REJECT.len()..],
+                        exec::gas_available() / 2,
+                        0,
+                    )
+                    .await;
+                    debug!("NFT receiver called the NFT contract: {:?}", reply.is_ok());
+                    NftReceiverReply::Rejected
+                }
+                _ => NftReceiverReply::Accepted,
+            }
+        }
+    };
+    msg::reply(reply, 0, 0);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn init() {}
@@ -0,0 +1,75 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub struct InitConfig {
+    /// The NFT contract whose tokens can be staked
+    pub nft_contract: ActorId,
+    /// The fungible token contract the rewards are paid in
+    pub reward_contract: ActorId,
+    /// The reward each staked token earns per block
+    pub reward_per_block: u128,
+    /// True to mint the rewards as an admin of the reward contract,
+    /// false to transfer them from the staking program balance
+    pub mint_rewards: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct Stake {
+    pub owner: ActorId,
+    /// The block the rewards of the token were last accrued at
+    pub since_block: u32,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Action {
+    Stake(U256),
+    Unstake(U256),
+    Claim,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    Staked {
+        account: ActorId,
+        token_id: U256,
+    },
+    /// The pending rewards are paid together with unstaking
+    Unstaked {
+        account: ActorId,
+        token_id: U256,
+        reward: u128,
+    },
+    RewardsClaimed {
+        account: ActorId,
+        amount: u128,
+    },
+    /// The reward contract refused the payment, the rewards stay pending
+    PaymentFailed {
+        account: ActorId,
+        amount: u128,
+    },
+    /// The NFT contract refused to return the token, it stays staked
+    UnstakeFailed {
+        account: ActorId,
+        token_id: U256,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum State {
+    Stake(U256),
+    StakedBy(ActorId),
+    PendingRewards(ActorId),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Stake(Option<Stake>),
+    StakedBy(Vec<U256>),
+    PendingRewards(u128),
+}
@@ -0,0 +1,228 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use fungible_token_messages::{Action as FTAction, Event as FTEvent, MintInput, TransferInput};
+use gstd::{debug, exec, msg, prelude::*, ActorId};
+use nft_example_client::{nft_owner, nft_transfer};
+use nft_staking_io::{Action, Event, InitConfig, Stake, State, StateReply};
+use primitive_types::U256;
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+const GAS_FOR_FT: u64 = 500_000_000;
+
+#[derive(Debug)]
+pub struct Staking {
+    pub nft_contract: ActorId,
+    pub reward_contract: ActorId,
+    pub reward_per_block: u128,
+    pub mint_rewards: bool,
+    /// Staked tokens, they are held by the staking program
+    pub stakes: BTreeMap<U256, Stake>,
+    /// Rewards that are accrued but not paid yet
+    pub rewards: BTreeMap<ActorId, u128>,
+}
+
+static mut STAKING: Staking = Staking {
+    nft_contract: ZERO_ID,
+    reward_contract: ZERO_ID,
+    reward_per_block: 0,
+    mint_rewards: false,
+    stakes: BTreeMap::new(),
+    rewards: BTreeMap::new(),
+};
+
+impl Staking {
+    /// Returns the rewards the stake has earned since its rewards were last accrued
+    fn earned(&self, stake: &Stake, block: u32) -> u128 {
+        (block.saturating_sub(stake.since_block) as u128).saturating_mul(self.reward_per_block)
+    }
+
+    /// Returns the accrued rewards of the account together with the rewards its tokens have earned since
+    fn pending_rewards(&self, account: &ActorId) -> u128 {
+        let block = exec::block_height();
+        self.stakes
+            .values()
+            .filter(|stake| stake.owner == *account)
+            .fold(
+                *self.rewards.get(account).unwrap_or(&0),
+                |pending, stake| pending.saturating_add(self.earned(stake, block)),
+            )
+    }
+
+    /// Moves the rewards the account's tokens have earned to its accrued rewards
+    fn accrue(&mut self, account: &ActorId) {
+        let pending = self.pending_rewards(account);
+        let block = exec::block_height();
+        for stake in self
+            .stakes
+            .values_mut()
+            .filter(|stake| stake.owner == *account)
+        {
+            stake.since_block = block;
+        }
+        self.rewards.insert(*account, pending);
+    }
+
+    /// Pays all accrued rewards of the account by minting or transferring the reward token
+    /// If the reward contract refuses the payment, the rewards stay accrued and their amount is returned as the error
+    async fn pay_rewards(&mut self, account: &ActorId) -> Result<u128, u128> {
+        // a claim running while this payment waits finds no rewards to pay again
+        let amount = self.rewards.remove(account).unwrap_or(0);
+        if amount == 0 {
+            return Ok(0);
+        }
+        let action = match self.mint_rewards {
+            true => FTAction::Mint(MintInput {
+                account: *account,
+                amount,
+            }),
+            false => FTAction::Transfer(TransferInput {
+                to: *account,
+                amount,
+            }),
+        };
+        let reply =
+            msg::send_and_wait_for_reply::<FTEvent, _>(self.reward_contract, action, GAS_FOR_FT, 0)
+                .await;
+        if reply.is_err() {
+            debug!("Staking: Unable to pay {:?} to {:?}", amount, account);
+            let rewards = self.rewards.entry(*account).or_default();
+            *rewards = rewards.saturating_add(amount);
+            return Err(amount);
+        }
+        Ok(amount)
+    }
+
+    /// Takes the caller's token, which must be approved to the staking program, and starts accruing rewards for it
+    async fn stake(&mut self, token_id: U256) {
+        let account = msg::source();
+        if self.stakes.contains_key(&token_id) {
+            panic!("Staking: Token is already staked");
+        }
+        let nft_contract = self.nft_contract;
+        if nft_owner(&nft_contract, token_id).await != Some(account) {
+            panic!("Staking: Only the token owner can stake it");
+        }
+        if !nft_transfer(&nft_contract, &exec::program_id(), token_id).await {
+            panic!("Staking: Error in transferring the token");
+        }
+        self.stakes.insert(
+            token_id,
+            Stake {
+                owner: account,
+                since_block: exec::block_height(),
+            },
+        );
+        msg::reply(Event::Staked { account, token_id }, 0, 0);
+    }
+
+    /// Returns the token to its owner and pays all pending rewards of the owner
+    /// The token stays staked if the NFT contract refuses to return it
+    async fn unstake(&mut self, token_id: U256) {
+        let account = msg::source();
+        let stake = self
+            .stakes
+            .get(&token_id)
+            .expect("Staking: Token is not staked");
+        if stake.owner != account {
+            panic!("Staking: Only the token owner can unstake it");
+        }
+
+        // the stake is kept until the token is back with its owner, so a failed transfer leaves it staked
+        let nft_contract = self.nft_contract;
+        if !nft_transfer(&nft_contract, &account, token_id).await {
+            debug!("Staking: Unable to return {:?} to {:?}", token_id, account);
+            msg::reply(Event::UnstakeFailed { account, token_id }, 0, 0);
+            return;
+        }
+        self.accrue(&account);
+        self.stakes.remove(&token_id);
+        let event = match self.pay_rewards(&account).await {
+            Ok(reward) => Event::Unstaked {
+                account,
+                token_id,
+                reward,
+            },
+            Err(amount) => Event::PaymentFailed { account, amount },
+        };
+        msg::reply(event, 0, 0);
+    }
+
+    /// Pays all pending rewards of the caller
+    async fn claim(&mut self) {
+        let account = msg::source();
+        self.accrue(&account);
+        let event = match self.pay_rewards(&account).await {
+            Ok(amount) => Event::RewardsClaimed { account, amount },
+            Err(amount) => Event::PaymentFailed { account, amount },
+        };
+        msg::reply(event, 0, 0);
+    }
+}
+
+gstd::metadata! {
+    title: "NFT Staking",
+        init:
+            input: InitConfig,
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: Action = msg::load().expect("Could not load Action");
+    let staking = unsafe { &mut STAKING };
+    match action {
+        Action::Stake(token_id) => {
+            staking.stake(token_id).await;
+        }
+        Action::Unstake(token_id) => {
+            staking.unstake(token_id).await;
+        }
+        Action::Claim => {
+            staking.claim().await;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn init() {
+    let config: InitConfig = msg::load().expect("Unable to decode InitConfig");
+    debug!("NFT Staking {:?}", config);
+    STAKING.nft_contract = config.nft_contract;
+    STAKING.reward_contract = config.reward_contract;
+    STAKING.reward_per_block = config.reward_per_block;
+    STAKING.mint_rewards = config.mint_rewards;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::Stake(token_id) => {
+            StateReply::Stake(STAKING.stakes.get(&token_id).cloned()).encode()
+        }
+        State::StakedBy(account) => {
+            let tokens = STAKING
+                .stakes
+                .iter()
+                .filter(|(_, stake)| stake.owner == account)
+                .map(|(token_id, _)| *token_id)
+                .collect();
+            StateReply::StakedBy(tokens).encode()
+        }
+        State::PendingRewards(account) => {
+            StateReply::PendingRewards(STAKING.pending_rewards(&account)).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
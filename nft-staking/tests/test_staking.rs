@@ -0,0 +1,247 @@
+use codec::{Decode, Encode};
+use fungible_token_messages::{
+    Action as FTAction, Event as FTEvent, InitConfig as FTInitConfig, MintInput,
+};
+use gtest::{Program, RunResult, System};
+use nft_example_io::{
+    Action as NFTAction, Event as NFTEvent, ExpiryConfig, InitConfig as NFTInitConfig,
+};
+use nft_staking_io::*;
+
+const USERS: &'static [u64] = &[4, 5, 6];
+const NFT_ID: u64 = 1;
+const FT_ID: u64 = 2;
+const STAKING_ID: u64 = 3;
+const REWARD_PER_BLOCK: u128 = 100;
+
+fn init(sys: &System, mint_rewards: bool) {
+    init_with_expiry(sys, mint_rewards, None);
+}
+
+fn init_with_expiry(sys: &System, mint_rewards: bool, expiry: Option<ExpiryConfig>) {
+    sys.init_logger();
+
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        NFTInitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            expiry,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], NFTAction::Mint);
+    assert!(!res.main_failed());
+
+    let ft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/fungible_token.wasm",
+    );
+    let res = ft.send(
+        USERS[0],
+        FTInitConfig {
+            name: String::from("MyRewardToken"),
+            symbol: String::from("MRT"),
+        },
+    );
+    assert!(res.log().is_empty());
+    if mint_rewards {
+        let res = ft.send(USERS[0], FTAction::AddAdmin(STAKING_ID.into()));
+        assert!(!res.main_failed());
+    }
+
+    let staking = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_staking.wasm",
+    );
+    let res = staking.send(
+        USERS[0],
+        InitConfig {
+            nft_contract: NFT_ID.into(),
+            reward_contract: FT_ID.into(),
+            reward_per_block: REWARD_PER_BLOCK,
+            mint_rewards,
+        },
+    );
+    assert!(res.log().is_empty());
+}
+
+fn stake(sys: &System) {
+    let nft = sys.get_program(NFT_ID);
+    let staking = sys.get_program(STAKING_ID);
+    let res = nft.send(
+        USERS[0],
+        NFTAction::Approve {
+            to: STAKING_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = staking.send(USERS[0], Action::Stake(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Staked {
+            account: USERS[0].into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    assert_owner(sys, STAKING_ID);
+}
+
+fn assert_owner(sys: &System, owner: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(USERS[2], NFTAction::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[2], NFTEvent::OwnerOf(owner.into()).encode())));
+}
+
+fn assert_ft_balance(sys: &System, account: u64, balance: u128) {
+    let ft = sys.get_program(FT_ID);
+    let res = ft.send(USERS[2], FTAction::BalanceOf(account.into()));
+    assert!(res.contains(&(USERS[2], FTEvent::Balance(balance).encode())));
+}
+
+// the rewards depend on the number of blocks the messages take, so they are read from the reply
+fn event(res: &RunResult) -> Event {
+    res.log()
+        .iter()
+        .find_map(|log| Event::decode(&mut log.payload()).ok())
+        .expect("No staking event in the reply")
+}
+
+#[test]
+fn stake_failures() {
+    let sys = System::new();
+    init(&sys, true);
+    let staking = sys.get_program(STAKING_ID);
+    // must fail since the staking program isn't approved
+    let res = staking.send(USERS[0], Action::Stake(0_i32.into()));
+    assert!(res.main_failed());
+    // must fail since the caller isn't the token owner
+    let res = staking.send(USERS[1], Action::Stake(0_i32.into()));
+    assert!(res.main_failed());
+    // must fail since the token isn't staked
+    let res = staking.send(USERS[0], Action::Unstake(0_i32.into()));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn stake_and_claim() {
+    let sys = System::new();
+    init(&sys, true);
+    stake(&sys);
+    let staking = sys.get_program(STAKING_ID);
+
+    sys.spend_blocks(10);
+    let res = staking.send(USERS[0], Action::Claim);
+    let claimed = match event(&res) {
+        Event::RewardsClaimed { account, amount } if account == USERS[0].into() => amount,
+        event => panic!("Unexpected event {:?}", event),
+    };
+    assert!(claimed >= 10 * REWARD_PER_BLOCK);
+    assert_ft_balance(&sys, USERS[0], claimed);
+
+    // must fail since the caller isn't the token owner
+    let res = staking.send(USERS[1], Action::Unstake(0_i32.into()));
+    assert!(res.main_failed());
+
+    sys.spend_blocks(10);
+    let res = staking.send(USERS[0], Action::Unstake(0_i32.into()));
+    let reward = match event(&res) {
+        Event::Unstaked {
+            account, reward, ..
+        } if account == USERS[0].into() => reward,
+        event => panic!("Unexpected event {:?}", event),
+    };
+    assert!(reward >= 10 * REWARD_PER_BLOCK);
+    assert_ft_balance(&sys, USERS[0], claimed + reward);
+    assert_owner(&sys, USERS[0]);
+
+    // nothing is earned after unstaking
+    sys.spend_blocks(10);
+    let res = staking.send(USERS[0], Action::Claim);
+    assert!(res.contains(&(
+        USERS[0],
+        Event::RewardsClaimed {
+            account: USERS[0].into(),
+            amount: 0,
+        }
+        .encode()
+    )));
+}
+
+#[test]
+fn unstake_expired_token() {
+    let sys = System::new();
+    init_with_expiry(
+        &sys,
+        true,
+        Some(ExpiryConfig {
+            period: 30_000,
+            extension_price: None,
+            transferable_when_expired: false,
+        }),
+    );
+    stake(&sys);
+    let staking = sys.get_program(STAKING_ID);
+
+    // the NFT contract refuses to return the expired token, so it stays staked and keeps earning
+    sys.spend_blocks(100);
+    let res = staking.send(USERS[0], Action::Unstake(0_i32.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        Event::UnstakeFailed {
+            account: USERS[0].into(),
+            token_id: 0_i32.into(),
+        }
+        .encode()
+    )));
+    assert_owner(&sys, STAKING_ID);
+    let res = staking.send(USERS[0], Action::Claim);
+    let claimed = match event(&res) {
+        Event::RewardsClaimed { account, amount } if account == USERS[0].into() => amount,
+        event => panic!("Unexpected event {:?}", event),
+    };
+    assert!(claimed >= 100 * REWARD_PER_BLOCK);
+}
+
+#[test]
+fn transfer_rewards() {
+    let sys = System::new();
+    init(&sys, false);
+    stake(&sys);
+    let staking = sys.get_program(STAKING_ID);
+
+    // the staking program has no reward tokens yet, so the rewards stay pending
+    sys.spend_blocks(10);
+    let res = staking.send(USERS[0], Action::Claim);
+    let pending = match event(&res) {
+        Event::PaymentFailed { account, amount } if account == USERS[0].into() => amount,
+        event => panic!("Unexpected event {:?}", event),
+    };
+    assert!(pending >= 10 * REWARD_PER_BLOCK);
+
+    let ft = sys.get_program(FT_ID);
+    let res = ft.send(
+        USERS[0],
+        FTAction::Mint(MintInput {
+            account: STAKING_ID.into(),
+            amount: 1_000_000,
+        }),
+    );
+    assert!(!res.main_failed());
+    let res = staking.send(USERS[0], Action::Claim);
+    let claimed = match event(&res) {
+        Event::RewardsClaimed { account, amount } if account == USERS[0].into() => amount,
+        event => panic!("Unexpected event {:?}", event),
+    };
+    assert!(claimed > pending);
+    assert_ft_balance(&sys, USERS[0], claimed);
+    assert_ft_balance(&sys, STAKING_ID, 1_000_000 - claimed);
+}
@@ -0,0 +1,56 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct Offer {
+    /// The account that makes the offer and gives `offered`
+    pub maker: ActorId,
+    /// The account that can accept the offer and gives `requested`
+    pub taker: ActorId,
+    /// The NFT contract and the token ID given by the maker
+    pub offered: (ActorId, U256),
+    /// The NFT contract and the token ID given by the taker
+    pub requested: (ActorId, U256),
+    /// The fungible token contract and the amount the maker pays in addition to the token
+    pub ft_amount: Option<(ActorId, u128)>,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Action {
+    MakeOffer {
+        taker: ActorId,
+        offered: (ActorId, U256),
+        requested: (ActorId, U256),
+        ft_amount: Option<(ActorId, u128)>,
+    },
+    CancelOffer(U256),
+    AcceptOffer(U256),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    OfferMade {
+        offer_id: U256,
+        offer: Offer,
+    },
+    OfferCancelled(U256),
+    Swapped(U256),
+    /// One of the legs failed, the moved assets were returned and the offer is still open
+    SwapFailed(U256),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum State {
+    Offer(U256),
+    OffersOf(ActorId),
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Offer(Option<Offer>),
+    OffersOf(Vec<(U256, Offer)>),
+}
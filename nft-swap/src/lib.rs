@@ -0,0 +1,206 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use fungible_token_messages::{Action as FTAction, Event as FTEvent, TransferFromInput};
+use gstd::{debug, exec, msg, prelude::*, ActorId};
+use nft_example_client::{nft_owner, nft_transfer};
+use nft_swap_io::{Action, Event, Offer, State, StateReply};
+use primitive_types::U256;
+
+const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
+const GAS_FOR_FT: u64 = 500_000_000;
+
+#[derive(Debug)]
+pub struct Swap {
+    pub offer_id: U256,
+    pub offers: BTreeMap<U256, Offer>,
+}
+
+static mut SWAP: Swap = Swap {
+    offer_id: U256::zero(),
+    offers: BTreeMap::new(),
+};
+
+/// Sends the token held by the swap program, which must not fail
+async fn release(token: &(ActorId, U256), to: &ActorId) {
+    if !nft_transfer(&token.0, to, token.1).await {
+        panic!("Swap: Error in releasing the token");
+    }
+}
+
+/// Returns false if the fungible token contract refused the transfer
+async fn ft_transfer_from(
+    ft_contract: &ActorId,
+    owner: &ActorId,
+    to: &ActorId,
+    amount: u128,
+) -> bool {
+    msg::send_and_wait_for_reply::<FTEvent, _>(
+        *ft_contract,
+        FTAction::TransferFrom(TransferFromInput {
+            owner: *owner,
+            to: *to,
+            amount,
+        }),
+        GAS_FOR_FT,
+        0,
+    )
+    .await
+    .is_ok()
+}
+
+impl Swap {
+    /// Records the offer to swap the caller's token for the token of `taker`
+    /// The tokens stay with their owners until the offer is accepted
+    async fn make_offer(
+        &mut self,
+        taker: ActorId,
+        offered: (ActorId, U256),
+        requested: (ActorId, U256),
+        ft_amount: Option<(ActorId, u128)>,
+    ) {
+        let maker = msg::source();
+        if taker == ZERO_ID || taker == maker {
+            panic!("Swap: Invalid taker");
+        }
+        if offered == requested {
+            panic!("Swap: Token can not be swapped for itself");
+        }
+        if let Some((_, 0)) = ft_amount {
+            panic!("Swap: Fungible token amount must be greater than zero");
+        }
+        if nft_owner(&offered.0, offered.1).await != Some(maker) {
+            panic!("Swap: Only the token owner can offer it");
+        }
+
+        let offer_id = self.offer_id;
+        self.offer_id = self.offer_id.saturating_add(U256::one());
+        let offer = Offer {
+            maker,
+            taker,
+            offered,
+            requested,
+            ft_amount,
+        };
+        self.offers.insert(offer_id, offer.clone());
+        msg::reply(Event::OfferMade { offer_id, offer }, 0, 0);
+    }
+
+    fn cancel_offer(&mut self, offer_id: U256) {
+        let offer = self
+            .offers
+            .get(&offer_id)
+            .expect("Swap: Offer does not exist");
+        if offer.maker != msg::source() {
+            panic!("Swap: Only the maker can cancel the offer");
+        }
+        self.offers.remove(&offer_id);
+        msg::reply(Event::OfferCancelled(offer_id), 0, 0);
+    }
+
+    /// Executes the swap, the maker and the taker must have approved the swap program for their tokens
+    /// and the maker must have approved it for the fungible token amount
+    /// Both tokens are held by the swap program until all legs succeed, so if any leg fails
+    /// the already moved tokens are returned and the offer stays open
+    async fn accept_offer(&mut self, offer_id: U256) {
+        let offer = self
+            .offers
+            .get(&offer_id)
+            .expect("Swap: Offer does not exist");
+        if offer.taker != msg::source() {
+            panic!("Swap: Only the taker can accept the offer");
+        }
+        // taking the offer out while the legs run keeps a second accept from starting on it
+        let offer = self
+            .offers
+            .remove(&offer_id)
+            .expect("Swap: Offer does not exist");
+        let swap = exec::program_id();
+        let (offered, requested) = (offer.offered, offer.requested);
+
+        if nft_owner(&offered.0, offered.1).await != Some(offer.maker)
+            || nft_owner(&requested.0, requested.1).await != Some(offer.taker)
+        {
+            return self.fail(offer_id, offer);
+        }
+        if !nft_transfer(&offered.0, &swap, offered.1).await {
+            return self.fail(offer_id, offer);
+        }
+        if !nft_transfer(&requested.0, &swap, requested.1).await {
+            release(&offered, &offer.maker).await;
+            return self.fail(offer_id, offer);
+        }
+        if let Some((ft_contract, amount)) = offer.ft_amount {
+            if !ft_transfer_from(&ft_contract, &offer.maker, &offer.taker, amount).await {
+                release(&offered, &offer.maker).await;
+                release(&requested, &offer.taker).await;
+                return self.fail(offer_id, offer);
+            }
+        }
+
+        release(&offered, &offer.taker).await;
+        release(&requested, &offer.maker).await;
+        msg::reply(Event::Swapped(offer_id), 0, 0);
+    }
+
+    fn fail(&mut self, offer_id: U256, offer: Offer) {
+        debug!("Swap: Offer {:?} failed", offer_id);
+        self.offers.insert(offer_id, offer);
+        msg::reply(Event::SwapFailed(offer_id), 0, 0);
+    }
+}
+
+gstd::metadata! {
+    title: "NFT Swap",
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: Action = msg::load().expect("Could not load Action");
+    let swap = unsafe { &mut SWAP };
+    match action {
+        Action::MakeOffer {
+            taker,
+            offered,
+            requested,
+            ft_amount,
+        } => {
+            swap.make_offer(taker, offered, requested, ft_amount).await;
+        }
+        Action::CancelOffer(offer_id) => {
+            swap.cancel_offer(offer_id);
+        }
+        Action::AcceptOffer(offer_id) => {
+            swap.accept_offer(offer_id).await;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::Offer(offer_id) => StateReply::Offer(SWAP.offers.get(&offer_id).cloned()).encode(),
+        State::OffersOf(account) => {
+            let offers = SWAP
+                .offers
+                .iter()
+                .filter(|(_, offer)| offer.maker == account || offer.taker == account)
+                .map(|(offer_id, offer)| (*offer_id, offer.clone()))
+                .collect();
+            StateReply::OffersOf(offers).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
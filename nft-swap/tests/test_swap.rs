@@ -0,0 +1,220 @@
+use codec::Encode;
+use fungible_token_messages::{
+    Action as FTAction, ApproveInput, Event as FTEvent, InitConfig as FTInitConfig, MintInput,
+};
+use gtest::{Program, System};
+use nft_example_io::{Action as NFTAction, Event as NFTEvent, InitConfig as NFTInitConfig};
+use nft_swap_io::*;
+
+const USERS: &'static [u64] = &[4, 5, 6];
+const NFT_ID: u64 = 1;
+const FT_ID: u64 = 2;
+const SWAP_ID: u64 = 3;
+
+fn init(sys: &System) {
+    sys.init_logger();
+
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        NFTInitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    // the first user owns the token 0 and the second user owns the token 1
+    let res = nft.send(USERS[0], NFTAction::Mint);
+    assert!(!res.main_failed());
+    let res = nft.send(USERS[1], NFTAction::Mint);
+    assert!(!res.main_failed());
+
+    let ft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/fungible_token.wasm",
+    );
+    let res = ft.send(
+        USERS[0],
+        FTInitConfig {
+            name: String::from("MyFungibleToken"),
+            symbol: String::from("MFT"),
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = ft.send(
+        USERS[0],
+        FTAction::Mint(MintInput {
+            account: USERS[0].into(),
+            amount: 1_000,
+        }),
+    );
+    assert!(!res.main_failed());
+
+    let swap = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_swap.wasm",
+    );
+    let res = swap.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+}
+
+fn offer() -> Offer {
+    Offer {
+        maker: USERS[0].into(),
+        taker: USERS[1].into(),
+        offered: (NFT_ID.into(), 0_i32.into()),
+        requested: (NFT_ID.into(), 1_i32.into()),
+        ft_amount: Some((FT_ID.into(), 100)),
+    }
+}
+
+fn make_offer(sys: &System) {
+    let swap = sys.get_program(SWAP_ID);
+    let offer = offer();
+    let res = swap.send(
+        USERS[0],
+        Action::MakeOffer {
+            taker: offer.taker,
+            offered: offer.offered,
+            requested: offer.requested,
+            ft_amount: offer.ft_amount,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::OfferMade {
+            offer_id: 0_i32.into(),
+            offer,
+        }
+        .encode()
+    )));
+}
+
+fn approve_nft(sys: &System, user: u64, token_id: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(
+        user,
+        NFTAction::Approve {
+            to: SWAP_ID.into(),
+            token_id: token_id.into(),
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+fn assert_owner(sys: &System, token_id: u64, owner: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(USERS[2], NFTAction::OwnerOf(token_id.into()));
+    assert!(res.contains(&(USERS[2], NFTEvent::OwnerOf(owner.into()).encode())));
+}
+
+#[test]
+fn make_offer_failures() {
+    let sys = System::new();
+    init(&sys);
+    let swap = sys.get_program(SWAP_ID);
+    let offer = offer();
+    // must fail since the caller doesn't own the offered token
+    let res = swap.send(
+        USERS[2],
+        Action::MakeOffer {
+            taker: offer.taker,
+            offered: offer.offered,
+            requested: offer.requested,
+            ft_amount: None,
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the maker can't be the taker
+    let res = swap.send(
+        USERS[0],
+        Action::MakeOffer {
+            taker: USERS[0].into(),
+            offered: offer.offered,
+            requested: offer.requested,
+            ft_amount: None,
+        },
+    );
+    assert!(res.main_failed());
+    // must fail since the token can't be swapped for itself
+    let res = swap.send(
+        USERS[0],
+        Action::MakeOffer {
+            taker: offer.taker,
+            offered: offer.offered,
+            requested: offer.offered,
+            ft_amount: None,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn swap() {
+    let sys = System::new();
+    init(&sys);
+    make_offer(&sys);
+    let swap = sys.get_program(SWAP_ID);
+
+    // must fail since the caller isn't the taker
+    let res = swap.send(USERS[2], Action::AcceptOffer(0_i32.into()));
+    assert!(res.main_failed());
+
+    // the taker hasn't approved the swap program, so the offered token is returned
+    approve_nft(&sys, USERS[0], 0);
+    let res = swap.send(USERS[1], Action::AcceptOffer(0_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::SwapFailed(0_i32.into()).encode())));
+    assert_owner(&sys, 0, USERS[0]);
+    assert_owner(&sys, 1, USERS[1]);
+
+    // the maker hasn't approved the fungible token, so both tokens are returned
+    approve_nft(&sys, USERS[0], 0);
+    approve_nft(&sys, USERS[1], 1);
+    let res = swap.send(USERS[1], Action::AcceptOffer(0_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::SwapFailed(0_i32.into()).encode())));
+    assert_owner(&sys, 0, USERS[0]);
+    assert_owner(&sys, 1, USERS[1]);
+
+    approve_nft(&sys, USERS[0], 0);
+    approve_nft(&sys, USERS[1], 1);
+    let ft = sys.get_program(FT_ID);
+    let res = ft.send(
+        USERS[0],
+        FTAction::Approve(ApproveInput {
+            spender: SWAP_ID.into(),
+            amount: 100,
+        }),
+    );
+    assert!(!res.main_failed());
+    let res = swap.send(USERS[1], Action::AcceptOffer(0_i32.into()));
+    assert!(res.contains(&(USERS[1], Event::Swapped(0_i32.into()).encode())));
+    assert_owner(&sys, 0, USERS[1]);
+    assert_owner(&sys, 1, USERS[0]);
+    let res = ft.send(USERS[2], FTAction::BalanceOf(USERS[1].into()));
+    assert!(res.contains(&(USERS[2], FTEvent::Balance(100).encode())));
+
+    // must fail since the offer is executed
+    let res = swap.send(USERS[1], Action::AcceptOffer(0_i32.into()));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn cancel_offer() {
+    let sys = System::new();
+    init(&sys);
+    make_offer(&sys);
+    let swap = sys.get_program(SWAP_ID);
+    // must fail since the caller isn't the maker
+    let res = swap.send(USERS[1], Action::CancelOffer(0_i32.into()));
+    assert!(res.main_failed());
+    let res = swap.send(USERS[0], Action::CancelOffer(0_i32.into()));
+    assert!(res.contains(&(USERS[0], Event::OfferCancelled(0_i32.into()).encode())));
+    // must fail since the offer is cancelled
+    let res = swap.send(USERS[1], Action::AcceptOffer(0_i32.into()));
+    assert!(res.main_failed());
+}
@@ -0,0 +1,81 @@
+#![no_std]
+
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+pub struct Vault {
+    /// The account that locked the token and received the shares
+    pub curator: ActorId,
+    pub nft_contract: ActorId,
+    pub token_id: U256,
+    /// The fungible token contract of the shares, the vault must be its admin
+    pub share_contract: ActorId,
+    /// The amount of shares that are not redeemed yet
+    pub shares: u128,
+    /// The value the whole token can be bought out for
+    pub reserve_price: u128,
+    /// The account that bought the token out
+    pub buyer: Option<ActorId>,
+    /// The buyout value that is not redeemed yet
+    pub proceeds: u128,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Action {
+    Fractionalize {
+        nft_contract: ActorId,
+        token_id: U256,
+        share_contract: ActorId,
+        shares: u128,
+        reserve_price: u128,
+    },
+    Buyout(U256),
+    Redeem {
+        vault_id: U256,
+        shares: u128,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum Event {
+    Fractionalized {
+        vault_id: U256,
+        vault: Vault,
+    },
+    BoughtOut {
+        vault_id: U256,
+        buyer: ActorId,
+        price: u128,
+    },
+    Redeemed {
+        vault_id: U256,
+        account: ActorId,
+        shares: u128,
+        payout: u128,
+    },
+    /// The NFT contract refused to transfer the token, the value is refunded to the buyer
+    BuyoutFailed {
+        vault_id: U256,
+        buyer: ActorId,
+    },
+    /// The shares couldn't be taken from the account, so they stay in circulation
+    RedeemFailed {
+        vault_id: U256,
+        account: ActorId,
+    },
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum State {
+    Vault(U256),
+    Vaults,
+}
+
+#[derive(Debug, Encode, Decode, TypeInfo)]
+pub enum StateReply {
+    Vault(Option<Vault>),
+    Vaults(Vec<(U256, Vault)>),
+}
@@ -0,0 +1,296 @@
+#![no_std]
+#![feature(const_btree_new)]
+
+use codec::Encode;
+use fungible_token_messages::{
+    Action as FTAction, BurnInput, Event as FTEvent, MintInput, TransferFromInput,
+};
+use gstd::{debug, exec, msg, prelude::*, ActorId};
+use nft_example_client::{nft_owner, nft_transfer};
+use nft_vault_io::{Action, Event, State, StateReply, Vault};
+use primitive_types::U256;
+
+const GAS_FOR_FT: u64 = 500_000_000;
+
+#[derive(Debug)]
+pub struct Vaults {
+    pub vault_id: U256,
+    pub vaults: BTreeMap<U256, Vault>,
+}
+
+static mut VAULTS: Vaults = Vaults {
+    vault_id: U256::zero(),
+    vaults: BTreeMap::new(),
+};
+
+async fn ft_total_supply(ft_contract: &ActorId) -> u128 {
+    let reply: FTEvent =
+        msg::send_and_wait_for_reply(*ft_contract, FTAction::TotalSupply, GAS_FOR_FT, 0)
+            .await
+            .expect("Vault: Error in getting the share supply");
+    match reply {
+        FTEvent::TotalSupply(total_supply) => total_supply,
+        _ => panic!("Vault: Unexpected reply from the share contract"),
+    }
+}
+
+async fn ft_minters(ft_contract: &ActorId) -> Vec<ActorId> {
+    let reply: FTEvent =
+        msg::send_and_wait_for_reply(*ft_contract, FTAction::Minters, GAS_FOR_FT, 0)
+            .await
+            .expect("Vault: Error in getting the share minters");
+    match reply {
+        FTEvent::Minters(minters) => minters,
+        _ => panic!("Vault: Unexpected reply from the share contract"),
+    }
+}
+
+/// Returns false if the fungible token contract refused the message
+async fn ft_send(ft_contract: &ActorId, action: FTAction) -> bool {
+    msg::send_and_wait_for_reply::<FTEvent, _>(*ft_contract, action, GAS_FOR_FT, 0)
+        .await
+        .is_ok()
+}
+
+impl Vaults {
+    /// Locks the caller's token, which must be approved to the vault, and mints `shares` to the caller
+    /// The share contract must be a fresh fungible token that only the vault can mint,
+    /// so the shares in circulation never exceed the shares of the vault
+    async fn fractionalize(
+        &mut self,
+        nft_contract: ActorId,
+        token_id: U256,
+        share_contract: ActorId,
+        shares: u128,
+        reserve_price: u128,
+    ) {
+        let curator = msg::source();
+        if shares == 0 {
+            panic!("Vault: Shares must be greater than zero");
+        }
+        if reserve_price == 0 {
+            panic!("Vault: Reserve price must be greater than zero");
+        }
+        if nft_owner(&nft_contract, token_id).await != Some(curator) {
+            panic!("Vault: Only the token owner can fractionalize it");
+        }
+        if ft_minters(&share_contract).await != vec![exec::program_id()] {
+            panic!("Vault: Vault must be the only minter of the share token");
+        }
+        if ft_total_supply(&share_contract).await != 0 {
+            panic!("Vault: Share token is already in use");
+        }
+        if !nft_transfer(&nft_contract, &exec::program_id(), token_id).await {
+            panic!("Vault: Error in transferring the token");
+        }
+        let minted = ft_send(
+            &share_contract,
+            FTAction::Mint(MintInput {
+                account: curator,
+                amount: shares,
+            }),
+        )
+        .await;
+        if !minted {
+            nft_transfer(&nft_contract, &curator, token_id).await;
+            panic!("Vault: Error in minting the shares");
+        }
+
+        let vault_id = self.vault_id;
+        self.vault_id = self.vault_id.saturating_add(U256::one());
+        let vault = Vault {
+            curator,
+            nft_contract,
+            token_id,
+            share_contract,
+            shares,
+            reserve_price,
+            buyer: None,
+            proceeds: 0,
+        };
+        self.vaults.insert(vault_id, vault.clone());
+        msg::reply(Event::Fractionalized { vault_id, vault }, 0, 0);
+    }
+
+    /// Sells the whole token for the attached value that must be equal to the reserve price
+    /// The value is refunded if the NFT contract refuses to transfer the token
+    async fn buyout(&mut self, vault_id: U256) {
+        let buyer = msg::source();
+        let vault = self
+            .vaults
+            .get_mut(&vault_id)
+            .expect("Vault: Vault does not exist");
+        if vault.buyer.is_some() {
+            panic!("Vault: Token is already bought out");
+        }
+        if msg::value() != vault.reserve_price {
+            panic!("Vault: Attached value must be equal to the reserve price");
+        }
+        // the buyer is set before the transfer, so nobody else can buy the token meanwhile
+        vault.buyer = Some(buyer);
+        vault.proceeds = vault.reserve_price;
+        let (nft_contract, token_id, price) =
+            (vault.nft_contract, vault.token_id, vault.reserve_price);
+
+        if !nft_transfer(&nft_contract, &buyer, token_id).await {
+            debug!("Vault: Unable to transfer {:?} to {:?}", token_id, buyer);
+            let vault = self
+                .vaults
+                .get_mut(&vault_id)
+                .expect("Vault: Vault does not exist");
+            vault.buyer = None;
+            vault.proceeds = 0;
+            msg::send_bytes(buyer, b"", 0, price);
+            msg::reply(Event::BuyoutFailed { vault_id, buyer }, 0, 0);
+            return;
+        }
+        msg::reply(
+            Event::BoughtOut {
+                vault_id,
+                buyer,
+                price,
+            },
+            0,
+            0,
+        );
+    }
+
+    /// Burns the caller's shares, which must be approved to the vault, and pays their part of the buyout
+    /// Nothing is redeemed if the shares can't be taken from the caller
+    async fn redeem(&mut self, vault_id: U256, shares: u128) {
+        let account = msg::source();
+        let vault = self
+            .vaults
+            .get_mut(&vault_id)
+            .expect("Vault: Vault does not exist");
+        if vault.buyer.is_none() {
+            panic!("Vault: Token is not bought out yet");
+        }
+        if shares == 0 {
+            panic!("Vault: Shares must be greater than zero");
+        }
+        if shares > vault.shares {
+            panic!("Vault: Shares exceed the shares of the vault");
+        }
+        // the shares and their payout leave the vault before the shares are taken, so a concurrent
+        // redemption can't pay them out again, the last redeemer gets the rest of the proceeds
+        let payout =
+            (U256::from(vault.proceeds) * U256::from(shares) / U256::from(vault.shares)).as_u128();
+        vault.shares = vault
+            .shares
+            .checked_sub(shares)
+            .expect("Vault: Shares underflow");
+        vault.proceeds = vault
+            .proceeds
+            .checked_sub(payout)
+            .expect("Vault: Proceeds underflow");
+        let share_contract = vault.share_contract;
+        let taken = ft_send(
+            &share_contract,
+            FTAction::TransferFrom(TransferFromInput {
+                owner: account,
+                to: exec::program_id(),
+                amount: shares,
+            }),
+        )
+        .await;
+        if !taken {
+            let vault = self
+                .vaults
+                .get_mut(&vault_id)
+                .expect("Vault: Vault does not exist");
+            vault.shares = vault.shares.saturating_add(shares);
+            vault.proceeds = vault.proceeds.saturating_add(payout);
+            msg::reply(Event::RedeemFailed { vault_id, account }, 0, 0);
+            return;
+        }
+        if payout > 0 {
+            msg::send_bytes(account, b"", 0, payout);
+        }
+
+        let burned = ft_send(
+            &share_contract,
+            FTAction::Burn(BurnInput {
+                account: exec::program_id(),
+                amount: shares,
+            }),
+        )
+        .await;
+        // the payout is already sent, the shares that can't be burned stay out of circulation with the vault
+        if !burned {
+            debug!("Vault: Unable to burn {:?} shares", shares);
+        }
+        msg::reply(
+            Event::Redeemed {
+                vault_id,
+                account,
+                shares,
+                payout,
+            },
+            0,
+            0,
+        );
+    }
+}
+
+gstd::metadata! {
+    title: "NFT Vault",
+        handle:
+            input: Action,
+            output: Event,
+        state:
+            input: State,
+            output: StateReply,
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: Action = msg::load().expect("Could not load Action");
+    let vaults = unsafe { &mut VAULTS };
+    match action {
+        Action::Fractionalize {
+            nft_contract,
+            token_id,
+            share_contract,
+            shares,
+            reserve_price,
+        } => {
+            vaults
+                .fractionalize(
+                    nft_contract,
+                    token_id,
+                    share_contract,
+                    shares,
+                    reserve_price,
+                )
+                .await;
+        }
+        Action::Buyout(vault_id) => {
+            vaults.buyout(vault_id).await;
+        }
+        Action::Redeem { vault_id, shares } => {
+            vaults.redeem(vault_id, shares).await;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn meta_state() -> *mut [i32; 2] {
+    let query: State = msg::load().expect("failed to decode input argument");
+    let encoded = match query {
+        State::Vault(vault_id) => StateReply::Vault(VAULTS.vaults.get(&vault_id).cloned()).encode(),
+        State::Vaults => {
+            let vaults = VAULTS
+                .vaults
+                .iter()
+                .map(|(vault_id, vault)| (*vault_id, vault.clone()))
+                .collect();
+            StateReply::Vaults(vaults).encode()
+        }
+    };
+    let result = gstd::macros::util::to_wasm_ptr(&(encoded[..]));
+
+    core::mem::forget(encoded);
+
+    result
+}
@@ -0,0 +1,331 @@
+use codec::Encode;
+use fungible_token_messages::{
+    Action as FTAction, ApproveInput, Event as FTEvent, InitConfig as FTInitConfig, MintInput,
+    TransferInput,
+};
+use gtest::{Program, System};
+use nft_example_io::{
+    Action as NFTAction, Event as NFTEvent, ExpiryConfig, InitConfig as NFTInitConfig,
+};
+use nft_vault_io::*;
+
+const USERS: &'static [u64] = &[4, 5, 6];
+const NFT_ID: u64 = 1;
+const SHARES_ID: u64 = 2;
+const VAULT_ID: u64 = 3;
+
+fn init(sys: &System) {
+    init_with_expiry(sys, None);
+}
+
+fn init_with_expiry(sys: &System, expiry: Option<ExpiryConfig>) {
+    sys.init_logger();
+
+    let nft = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_example.wasm",
+    );
+    let res = nft.send(
+        USERS[0],
+        NFTInitConfig {
+            name: String::from("MyToken"),
+            symbol: String::from("MTK"),
+            max_royalty_rate: 1_000,
+            expiry,
+            ..Default::default()
+        },
+    );
+    assert!(res.log().is_empty());
+    let res = nft.send(USERS[0], NFTAction::Mint);
+    assert!(!res.main_failed());
+
+    let shares = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/fungible_token.wasm",
+    );
+    let res = shares.send(
+        USERS[0],
+        FTInitConfig {
+            name: String::from("MyTokenShares"),
+            symbol: String::from("MTS"),
+        },
+    );
+    assert!(res.log().is_empty());
+
+    let vault = Program::from_file(
+        &sys,
+        "../../apps/target/wasm32-unknown-unknown/release/nft_vault.wasm",
+    );
+    let res = vault.send_bytes(USERS[0], b"");
+    assert!(res.log().is_empty());
+}
+
+/// Makes the vault the only minter of the share token
+fn hand_over_shares(sys: &System) {
+    let shares = sys.get_program(SHARES_ID);
+    let res = shares.send(USERS[0], FTAction::TransferCreator(VAULT_ID.into()));
+    assert!(res.contains(&(
+        USERS[0],
+        FTEvent::CreatorTransferred(VAULT_ID.into()).encode()
+    )));
+}
+
+fn fractionalize() -> Action {
+    Action::Fractionalize {
+        nft_contract: NFT_ID.into(),
+        token_id: 0_i32.into(),
+        share_contract: SHARES_ID.into(),
+        shares: 1_000,
+        reserve_price: 10_000,
+    }
+}
+
+fn approve_nft(sys: &System) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(
+        USERS[0],
+        NFTAction::Approve {
+            to: VAULT_ID.into(),
+            token_id: 0_i32.into(),
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+fn approve_shares(sys: &System, user: u64, amount: u128) {
+    let shares = sys.get_program(SHARES_ID);
+    let res = shares.send(
+        user,
+        FTAction::Approve(ApproveInput {
+            spender: VAULT_ID.into(),
+            amount,
+        }),
+    );
+    assert!(!res.main_failed());
+}
+
+fn assert_owner(sys: &System, owner: u64) {
+    let nft = sys.get_program(NFT_ID);
+    let res = nft.send(USERS[2], NFTAction::OwnerOf(0_i32.into()));
+    assert!(res.contains(&(USERS[2], NFTEvent::OwnerOf(owner.into()).encode())));
+}
+
+fn assert_shares(sys: &System, account: u64, balance: u128) {
+    let shares = sys.get_program(SHARES_ID);
+    let res = shares.send(USERS[2], FTAction::BalanceOf(account.into()));
+    assert!(res.contains(&(USERS[2], FTEvent::Balance(balance).encode())));
+}
+
+#[test]
+fn fractionalize_failures() {
+    let sys = System::new();
+    init(&sys);
+    let vault = sys.get_program(VAULT_ID);
+    // must fail since the vault isn't approved
+    let res = vault.send(USERS[0], fractionalize());
+    assert!(res.main_failed());
+    approve_nft(&sys);
+    // must fail since the caller isn't the token owner
+    let res = vault.send(USERS[1], fractionalize());
+    assert!(res.main_failed());
+
+    // must fail since the curator can still mint the shares
+    let res = vault.send(USERS[0], fractionalize());
+    assert!(res.main_failed());
+
+    // must fail since the share token is already in use
+    let shares = sys.get_program(SHARES_ID);
+    let res = shares.send(
+        USERS[0],
+        FTAction::Mint(MintInput {
+            account: USERS[0].into(),
+            amount: 1,
+        }),
+    );
+    assert!(!res.main_failed());
+    hand_over_shares(&sys);
+    let res = vault.send(USERS[0], fractionalize());
+    assert!(res.main_failed());
+    assert_owner(&sys, USERS[0]);
+}
+
+#[test]
+fn buyout_and_redeem() {
+    let sys = System::new();
+    init(&sys);
+    hand_over_shares(&sys);
+    approve_nft(&sys);
+    let vault = sys.get_program(VAULT_ID);
+    let res = vault.send(USERS[0], fractionalize());
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Fractionalized {
+            vault_id: 0_i32.into(),
+            vault: Vault {
+                curator: USERS[0].into(),
+                nft_contract: NFT_ID.into(),
+                token_id: 0_i32.into(),
+                share_contract: SHARES_ID.into(),
+                shares: 1_000,
+                reserve_price: 10_000,
+                buyer: None,
+                proceeds: 0,
+            },
+        }
+        .encode()
+    )));
+    assert_owner(&sys, VAULT_ID);
+    assert_shares(&sys, USERS[0], 1_000);
+
+    let shares = sys.get_program(SHARES_ID);
+    let res = shares.send(
+        USERS[0],
+        FTAction::Transfer(TransferInput {
+            to: USERS[1].into(),
+            amount: 400,
+        }),
+    );
+    assert!(!res.main_failed());
+
+    // must fail since the token isn't bought out yet
+    approve_shares(&sys, USERS[1], 400);
+    let res = vault.send(
+        USERS[1],
+        Action::Redeem {
+            vault_id: 0_i32.into(),
+            shares: 400,
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since the value isn't equal to the reserve price
+    let res = vault.send_with_value(USERS[2], Action::Buyout(0_i32.into()), 9_999);
+    assert!(res.main_failed());
+    let res = vault.send_with_value(USERS[2], Action::Buyout(0_i32.into()), 10_000);
+    assert!(res.contains(&(
+        USERS[2],
+        Event::BoughtOut {
+            vault_id: 0_i32.into(),
+            buyer: USERS[2].into(),
+            price: 10_000,
+        }
+        .encode()
+    )));
+    assert_owner(&sys, USERS[2]);
+    // must fail since the token is already bought out
+    let res = vault.send_with_value(USERS[1], Action::Buyout(0_i32.into()), 10_000);
+    assert!(res.main_failed());
+
+    let res = vault.send(
+        USERS[1],
+        Action::Redeem {
+            vault_id: 0_i32.into(),
+            shares: 400,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[1],
+        Event::Redeemed {
+            vault_id: 0_i32.into(),
+            account: USERS[1].into(),
+            shares: 400,
+            payout: 4_000,
+        }
+        .encode()
+    )));
+    assert_shares(&sys, USERS[1], 0);
+
+    // must fail since only 600 shares are left in the vault
+    approve_shares(&sys, USERS[0], 700);
+    let res = vault.send(
+        USERS[0],
+        Action::Redeem {
+            vault_id: 0_i32.into(),
+            shares: 700,
+        },
+    );
+    assert!(res.main_failed());
+    approve_shares(&sys, USERS[0], 0);
+
+    // the shares aren't approved, so they stay in circulation and can be redeemed later
+    let res = vault.send(
+        USERS[0],
+        Action::Redeem {
+            vault_id: 0_i32.into(),
+            shares: 600,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::RedeemFailed {
+            vault_id: 0_i32.into(),
+            account: USERS[0].into(),
+        }
+        .encode()
+    )));
+    approve_shares(&sys, USERS[0], 600);
+    let res = vault.send(
+        USERS[0],
+        Action::Redeem {
+            vault_id: 0_i32.into(),
+            shares: 600,
+        },
+    );
+    assert!(res.contains(&(
+        USERS[0],
+        Event::Redeemed {
+            vault_id: 0_i32.into(),
+            account: USERS[0].into(),
+            shares: 600,
+            payout: 6_000,
+        }
+        .encode()
+    )));
+    assert_shares(&sys, USERS[0], 0);
+    assert_shares(&sys, VAULT_ID, 0);
+}
+
+#[test]
+fn buyout_expired_token() {
+    let sys = System::new();
+    init_with_expiry(
+        &sys,
+        Some(ExpiryConfig {
+            period: 30_000,
+            extension_price: None,
+            transferable_when_expired: false,
+        }),
+    );
+    hand_over_shares(&sys);
+    approve_nft(&sys);
+    let vault = sys.get_program(VAULT_ID);
+    let res = vault.send(USERS[0], fractionalize());
+    assert!(!res.main_failed());
+
+    // the NFT contract refuses to transfer the expired token,
+    // so the buyer gets the value back and the vault isn't bought out
+    sys.spend_blocks(100);
+    let res = vault.send_with_value(USERS[2], Action::Buyout(0_i32.into()), 10_000);
+    assert!(res.contains(&(
+        USERS[2],
+        Event::BuyoutFailed {
+            vault_id: 0_i32.into(),
+            buyer: USERS[2].into(),
+        }
+        .encode()
+    )));
+    assert!(res.contains(&(USERS[2], Vec::<u8>::new())));
+    assert_owner(&sys, VAULT_ID);
+
+    // must fail since the token isn't bought out
+    approve_shares(&sys, USERS[0], 1_000);
+    let res = vault.send(
+        USERS[0],
+        Action::Redeem {
+            vault_id: 0_i32.into(),
+            shares: 1_000,
+        },
+    );
+    assert!(res.main_failed());
+    assert_shares(&sys, USERS[0], 1_000);
+}
@@ -7,7 +7,6 @@ pub trait NonFungibleTokenBase {
     /// * `name`: A descriptive name for a collection of NFTs in this contract
     /// * `symbol`: An abbreviated name for NFTs in this contract
     /// * `base_uri`: The URI of the NFT. This could be a website link, an API call, something on IPFS, some other unique identifier, etc
-    /// * `price`: The price of the NFT. 
     fn init(&mut self, name: String, symbol: String, base_uri: String);
 
     /// Transfer an NFT item from current owner to the new one
@@ -16,6 +15,8 @@ pub trait NonFungibleTokenBase {
     /// * `from`: the valid ActorId. It can the the token owner or the actor with the right to transfer the token
     /// * `to`: the valid ActorId, the account to which the token will be sent
     /// Contract must panic if `from` is neither the token owner nor the approved actor for the token. It also must panic if `to` is a zero ID
+    /// The balance of the token owner is decreased even if `from` is an approved actor or operator,
+    /// and the approval of the token is cleared
     fn transfer(&mut self, from: &ActorId, to: &ActorId, token_id: U256);
 
     /// Gives a right to the actor to manage the specific token
@@ -24,6 +25,7 @@ pub trait NonFungibleTokenBase {
     /// * `owner`: the valid ActorId that must be the token owner
     /// * `spender`: the valid ActorId that will be approved to manage the token
     /// Contract must panic if `owner` is not the token owner or `spender` is a zero ID
+    /// It also must panic while the token waits for the receiver's reply to `safe_transfer`
     fn approve(&mut self, owner: &ActorId, spender: &ActorId, token_id: U256);
 
     /// Enables or disables the actor to manage all the tokens the owner has
@@ -0,0 +1,41 @@
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+
+pub trait NonFungibleTokenComposable {
+    /// Nests the token into the parent token, the parent can belong to this or another program
+    /// The nested token and its children move together with the parent until they are detached
+    /// While the token is nested, it can't be transferred by `transfer`
+    /// Arguments:
+    /// * `caller`: the valid ActorId that must be the token owner or the actor with the right to manage the token
+    /// * `token_id`: the token ID
+    /// * `parent`: the program and the ID of the parent token
+    /// Contract must panic if the token or the parent of this program does not exist,
+    /// if `caller` is not authorized, the token or a token nested into it is soulbound,
+    /// the token is locked, the parent is nested into the token, the parent program is the zero address
+    /// or the token or the parent waits for the receiver's reply to `safe_transfer`
+    fn nest(&mut self, caller: &ActorId, token_id: U256, parent: (ActorId, U256));
+
+    /// Takes the nested token out of its parent and gives it to `to` together with its children
+    /// Arguments:
+    /// * `caller`: the valid ActorId that must be the root owner or the actor with the right to manage the token
+    /// * `root_owner`: the owner of the topmost token, resolved through the other programs
+    /// if the tree is nested into their tokens
+    /// * `token_id`: the token ID
+    /// * `to`: the new owner of the token
+    /// Contract must panic if the token is not nested or locked, the token or a token nested into it is soulbound,
+    /// `caller` is not authorized, `to` is the zero address or the token waits for the receiver's reply to `safe_transfer`
+    fn detach(&mut self, caller: &ActorId, root_owner: &ActorId, token_id: U256, to: &ActorId);
+
+    /// Returns the program and the ID of the token the token is nested into
+    fn parent_of(&self, token_id: U256) -> Option<(ActorId, U256)>;
+
+    /// Returns the tokens of this program that are nested into the token directly
+    fn children_of(&self, token_id: U256) -> Vec<U256>;
+
+    /// Returns the topmost token of this program the token is nested into, or the token itself
+    fn root_token(&self, token_id: U256) -> U256;
+
+    /// Returns the owner of the root token
+    /// If the root token is nested into a token of another program, it returns that program
+    fn root_owner(&self, token_id: U256) -> ActorId;
+}
@@ -5,13 +5,30 @@ use codec::{Decode, Encode};
 use gstd::{exec, msg, prelude::*, ActorId};
 pub mod base;
 use base::NonFungibleTokenBase;
+pub mod composable;
+use composable::NonFungibleTokenComposable;
+pub mod lock;
+use lock::NonFungibleTokenLockable;
+pub mod metadata;
+use metadata::NonFungibleTokenMetadata;
+pub mod provenance;
+use provenance::{NonFungibleTokenProvenance, OwnershipRecord, MAX_HISTORY_LEN};
+pub mod receiver;
+use receiver::{NftReceiverAction, NftReceiverReply};
+pub mod rental;
+use rental::{NonFungibleTokenRental, UserInfo};
+pub mod royalty;
+use royalty::{royalty_amount, NonFungibleTokenRoyalty, RoyaltyInfo, MAX_BASIS_POINTS};
+pub mod soulbound;
+use soulbound::NonFungibleTokenSoulbound;
 pub mod token;
-use token::TokenMetadata;
+use token::{AttributeValue, TokenMetadata};
 
 use primitive_types::U256;
 use scale_info::TypeInfo;
 
 const GAS_RESERVE: u64 = 500_000_000;
+const GAS_FOR_RECEIVER: u64 = 300_000_000;
 const ZERO_ID: ActorId = ActorId::new([0u8; 32]);
 
 #[derive(Debug)]
@@ -24,6 +41,19 @@ pub struct NonFungibleToken {
     pub token_approvals: BTreeMap<U256, ActorId>,
     pub balances: BTreeMap<ActorId, U256>,
     pub operator_approval: BTreeMap<ActorId, ActorId>,
+    pub default_royalty: Option<RoyaltyInfo>,
+    pub royalty_by_id: BTreeMap<U256, RoyaltyInfo>,
+    pub royalty_shares_by_id: BTreeMap<U256, Vec<(ActorId, u16)>>,
+    pub soulbound: bool,
+    pub soulbound_tokens: BTreeSet<U256>,
+    pub user_by_id: BTreeMap<U256, UserInfo>,
+    pub metadata_frozen: bool,
+    pub frozen_metadata: BTreeSet<U256>,
+    pub parent_by_id: BTreeMap<U256, (ActorId, U256)>,
+    pub children_by_id: BTreeMap<U256, BTreeSet<U256>>,
+    pub locked_by: BTreeMap<U256, ActorId>,
+    pub history_by_id: BTreeMap<U256, Vec<OwnershipRecord>>,
+    pub pending_transfers: BTreeSet<U256>,
 }
 
 impl NonFungibleTokenBase for NonFungibleToken {
@@ -34,35 +64,8 @@ impl NonFungibleTokenBase for NonFungibleToken {
     }
 
     fn transfer(&mut self, from: &ActorId, to: &ActorId, token_id: U256) {
-        if !self.exists(token_id) {
-            panic!("NonFungibleToken: token does not exist");
-        }
-        if from == to {
-            panic!("NonFungibleToken: Transfer to current owner");
-        }
-        if to == &ZERO_ID {
-            panic!("NonFungibleToken: Transfer to zero address.");
-        }
-
-        match self.authorized_actor(token_id, from) {
-            AuthAccount::None => {
-                panic!("NonFungibleToken: is not an authorized source");
-            }
-            AuthAccount::ApprovedActor => {
-                self.token_approvals.remove(&token_id);
-            }
-            _ => {}
-        }
-
-        let from_balance = *self.balances.get(from).unwrap_or(&U256::zero());
-        let to_balance = *self.balances.get(to).unwrap_or(&U256::zero());
-
-        self.balances
-            .insert(*from, from_balance.saturating_sub(U256::one()));
-        self.balances
-            .insert(*to, to_balance.saturating_add(U256::one()));
-
-        self.owner_by_id.insert(token_id, *to);
+        let owner = self.check_transfer(from, to, token_id);
+        self.move_token(&owner, to, token_id);
 
         msg::reply(
             Event::Transfer {
@@ -85,6 +88,11 @@ impl NonFungibleTokenBase for NonFungibleToken {
         if !self.is_token_owner(token_id, owner) {
             panic!("NonFungibleToken: is not owner");
         }
+        if self.is_soulbound(token_id) {
+            panic!("NonFungibleToken: token is soulbound");
+        }
+        self.check_unlocked(token_id);
+        self.check_not_pending(token_id);
 
         self.token_approvals.insert(token_id, *spender);
 
@@ -103,6 +111,9 @@ impl NonFungibleTokenBase for NonFungibleToken {
         if operator == &ZERO_ID {
             panic!("NonFungibleToken: Approval for a zero address");
         }
+        if self.soulbound {
+            panic!("NonFungibleToken: collection is soulbound");
+        }
         match approved {
             true => self.operator_approval.insert(*owner, *operator),
             false => self.operator_approval.remove(owner),
@@ -138,6 +149,342 @@ impl NonFungibleTokenBase for NonFungibleToken {
     }
 }
 
+impl NonFungibleTokenRoyalty for NonFungibleToken {
+    fn set_default_royalty(&mut self, receiver: &ActorId, rate: u16) {
+        if rate > MAX_BASIS_POINTS {
+            panic!("NonFungibleToken: royalty rate exceeds MAX_BASIS_POINTS");
+        }
+        self.default_royalty = Some(RoyaltyInfo {
+            receiver: *receiver,
+            rate,
+        });
+    }
+
+    fn delete_default_royalty(&mut self) {
+        self.default_royalty = None;
+    }
+
+    fn set_token_royalty(&mut self, token_id: U256, receiver: &ActorId, rate: u16) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        if rate > MAX_BASIS_POINTS {
+            panic!("NonFungibleToken: royalty rate exceeds MAX_BASIS_POINTS");
+        }
+        self.royalty_shares_by_id.remove(&token_id);
+        self.royalty_by_id.insert(
+            token_id,
+            RoyaltyInfo {
+                receiver: *receiver,
+                rate,
+            },
+        );
+    }
+
+    fn set_royalty_shares(&mut self, token_id: U256, shares: Vec<(ActorId, u16)>) {
+        if shares.iter().any(|(receiver, _)| receiver == &ZERO_ID) {
+            panic!("NonFungibleToken: royalty share for zero address");
+        }
+        let rate = shares
+            .iter()
+            .try_fold(0u16, |total, (_, rate)| total.checked_add(*rate))
+            .expect("NonFungibleToken: royalty rate exceeds MAX_BASIS_POINTS");
+        let receiver = shares
+            .first()
+            .map(|(receiver, _)| *receiver)
+            .expect("NonFungibleToken: no royalty shares");
+        self.set_token_royalty(token_id, &receiver, rate);
+        self.royalty_shares_by_id.insert(token_id, shares);
+    }
+
+    fn reset_token_royalty(&mut self, token_id: U256) {
+        self.royalty_by_id.remove(&token_id);
+        self.royalty_shares_by_id.remove(&token_id);
+    }
+
+    fn royalty_info(&self, token_id: U256, sale_price: u128) -> (ActorId, u128) {
+        match self
+            .royalty_by_id
+            .get(&token_id)
+            .or(self.default_royalty.as_ref())
+        {
+            Some(royalty) => (royalty.receiver, royalty_amount(sale_price, royalty.rate)),
+            None => (ZERO_ID, 0),
+        }
+    }
+
+    fn royalty_breakdown(&self, token_id: U256, sale_price: u128) -> Vec<(ActorId, u128)> {
+        match self.royalty_shares_by_id.get(&token_id) {
+            Some(shares) => shares
+                .iter()
+                .map(|(receiver, rate)| (*receiver, royalty_amount(sale_price, *rate)))
+                .collect(),
+            None => vec![self.royalty_info(token_id, sale_price)],
+        }
+    }
+}
+
+impl NonFungibleTokenSoulbound for NonFungibleToken {
+    fn set_soulbound(&mut self, soulbound: bool) {
+        self.soulbound = soulbound;
+    }
+
+    fn set_token_soulbound(&mut self, token_id: U256, soulbound: bool) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        match soulbound {
+            true => self.soulbound_tokens.insert(token_id),
+            false => self.soulbound_tokens.remove(&token_id),
+        };
+    }
+
+    fn is_soulbound(&self, token_id: U256) -> bool {
+        self.soulbound || self.soulbound_tokens.contains(&token_id)
+    }
+}
+
+impl NonFungibleTokenRental for NonFungibleToken {
+    fn set_user(&mut self, caller: &ActorId, token_id: U256, user: &ActorId, expires: u64) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        if let AuthAccount::None = self.authorized_actor(token_id, caller) {
+            panic!("NonFungibleToken: is not an authorized source");
+        }
+        self.check_not_pending(token_id);
+        if user == &ZERO_ID {
+            self.user_by_id.remove(&token_id);
+            return;
+        }
+        self.user_by_id.insert(
+            token_id,
+            UserInfo {
+                user: *user,
+                expires,
+            },
+        );
+    }
+
+    fn user_of(&self, token_id: U256) -> ActorId {
+        match self.user_by_id.get(&token_id) {
+            Some(info) if info.expires >= exec::block_timestamp() => info.user,
+            _ => ZERO_ID,
+        }
+    }
+
+    fn user_expires(&self, token_id: U256) -> u64 {
+        self.user_by_id
+            .get(&token_id)
+            .map(|info| info.expires)
+            .unwrap_or(0)
+    }
+}
+
+impl NonFungibleTokenMetadata for NonFungibleToken {
+    fn set_metadata(&mut self, token_id: U256, metadata: TokenMetadata) {
+        self.check_metadata_mutable(token_id);
+        self.token_metadata_by_id.insert(token_id, metadata);
+    }
+
+    fn set_attribute(&mut self, token_id: U256, key: String, value: AttributeValue) {
+        self.check_metadata_mutable(token_id);
+        let attributes = &mut self
+            .token_metadata_by_id
+            .entry(token_id)
+            .or_default()
+            .attributes;
+        match attributes.iter_mut().find(|(name, _)| name == &key) {
+            Some((_, current)) => *current = value,
+            None => attributes.push((key, value)),
+        }
+    }
+
+    fn remove_attribute(&mut self, token_id: U256, key: String) {
+        self.check_metadata_mutable(token_id);
+        if let Some(metadata) = self.token_metadata_by_id.get_mut(&token_id) {
+            metadata.attributes.retain(|(name, _)| name != &key);
+        }
+    }
+
+    fn freeze_metadata(&mut self, token_id: U256) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        self.frozen_metadata.insert(token_id);
+    }
+
+    fn freeze_all_metadata(&mut self) {
+        self.metadata_frozen = true;
+    }
+
+    fn is_metadata_frozen(&self, token_id: U256) -> bool {
+        self.metadata_frozen || self.frozen_metadata.contains(&token_id)
+    }
+}
+
+impl NonFungibleTokenLockable for NonFungibleToken {
+    fn lock(&mut self, caller: &ActorId, token_id: U256, locker: &ActorId) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        if let AuthAccount::None = self.authorized_actor(token_id, caller) {
+            panic!("NonFungibleToken: is not an authorized source");
+        }
+        if locker == &ZERO_ID {
+            panic!("NonFungibleToken: Lock by zero address");
+        }
+        self.check_unlocked(token_id);
+        self.check_not_pending(token_id);
+        self.locked_by.insert(token_id, *locker);
+    }
+
+    fn unlock(&mut self, caller: &ActorId, token_id: U256) {
+        match self.locked_by.get(&token_id) {
+            Some(locker) if locker == caller => {
+                self.locked_by.remove(&token_id);
+            }
+            Some(_) => panic!("NonFungibleToken: Only the locker can unlock the token"),
+            None => panic!("NonFungibleToken: token is not locked"),
+        }
+    }
+
+    fn locker_of(&self, token_id: U256) -> Option<ActorId> {
+        self.locked_by.get(&token_id).copied()
+    }
+}
+
+impl NonFungibleTokenProvenance for NonFungibleToken {
+    fn record_ownership(
+        &mut self,
+        token_id: U256,
+        from: &ActorId,
+        to: &ActorId,
+        price: Option<u128>,
+    ) {
+        let history = self.history_by_id.entry(token_id).or_default();
+        if history.len() >= MAX_HISTORY_LEN {
+            history.remove(0);
+        }
+        history.push(OwnershipRecord {
+            from: *from,
+            to: *to,
+            block_height: exec::block_height(),
+            price,
+        });
+    }
+
+    fn token_history(&self, token_id: U256, offset: u32, limit: u32) -> Vec<OwnershipRecord> {
+        self.history_by_id
+            .get(&token_id)
+            .map(|history| {
+                history
+                    .iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl NonFungibleTokenComposable for NonFungibleToken {
+    fn nest(&mut self, caller: &ActorId, token_id: U256, parent: (ActorId, U256)) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        if let AuthAccount::None = self.authorized_actor(token_id, caller) {
+            panic!("NonFungibleToken: is not an authorized source");
+        }
+        self.check_not_soulbound(token_id);
+        self.check_tree_unlocked(token_id);
+        self.check_not_pending(token_id);
+        let (program, parent_id) = parent;
+        let local = program == exec::program_id();
+        let new_owner = if local {
+            if !self.exists(parent_id) {
+                panic!("NonFungibleToken: parent token does not exist");
+            }
+            if parent_id == token_id || self.descendants(token_id).contains(&parent_id) {
+                panic!("NonFungibleToken: token can not be nested into itself");
+            }
+            self.check_not_pending(parent_id);
+            self.root_owner(parent_id)
+        } else {
+            if program == ZERO_ID {
+                panic!("NonFungibleToken: Nesting into zero address");
+            }
+            program
+        };
+
+        self.unlink(token_id);
+        let owner = *self.owner_by_id.get(&token_id).unwrap_or(&ZERO_ID);
+        if owner != new_owner {
+            self.move_token(&owner, &new_owner, token_id);
+        }
+        if local {
+            self.children_by_id
+                .entry(parent_id)
+                .or_default()
+                .insert(token_id);
+        }
+        self.parent_by_id.insert(token_id, parent);
+    }
+
+    fn detach(&mut self, caller: &ActorId, root_owner: &ActorId, token_id: U256, to: &ActorId) {
+        if !self.parent_by_id.contains_key(&token_id) {
+            panic!("NonFungibleToken: token is not nested");
+        }
+        if to == &ZERO_ID {
+            panic!("NonFungibleToken: Transfer to zero address.");
+        }
+        if caller != root_owner {
+            if let AuthAccount::None = self.authorized_actor(token_id, caller) {
+                panic!("NonFungibleToken: is not an authorized source");
+            }
+        }
+        self.check_not_soulbound(token_id);
+        self.check_tree_unlocked(token_id);
+        self.check_not_pending(token_id);
+        self.unlink(token_id);
+        let owner = *self.owner_by_id.get(&token_id).unwrap_or(&ZERO_ID);
+        if owner != *to {
+            self.move_token(&owner, to, token_id);
+        }
+    }
+
+    fn parent_of(&self, token_id: U256) -> Option<(ActorId, U256)> {
+        self.parent_by_id.get(&token_id).copied()
+    }
+
+    fn children_of(&self, token_id: U256) -> Vec<U256> {
+        self.children_by_id
+            .get(&token_id)
+            .map(|children| children.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn root_token(&self, token_id: U256) -> U256 {
+        let program = exec::program_id();
+        let mut root = token_id;
+        while let Some((parent_program, parent_id)) = self.parent_by_id.get(&root) {
+            if *parent_program != program {
+                break;
+            }
+            root = *parent_id;
+        }
+        root
+    }
+
+    fn root_owner(&self, token_id: U256) -> ActorId {
+        *self
+            .owner_by_id
+            .get(&self.root_token(token_id))
+            .unwrap_or(&ZERO_ID)
+    }
+}
+
 impl NonFungibleToken {
     pub const fn new() -> NonFungibleToken {
         NonFungibleToken {
@@ -149,6 +496,19 @@ impl NonFungibleToken {
             token_approvals: BTreeMap::new(),
             balances: BTreeMap::new(),
             operator_approval: BTreeMap::new(),
+            default_royalty: None,
+            royalty_by_id: BTreeMap::new(),
+            royalty_shares_by_id: BTreeMap::new(),
+            soulbound: false,
+            soulbound_tokens: BTreeSet::new(),
+            user_by_id: BTreeMap::new(),
+            metadata_frozen: false,
+            frozen_metadata: BTreeSet::new(),
+            parent_by_id: BTreeMap::new(),
+            children_by_id: BTreeMap::new(),
+            locked_by: BTreeMap::new(),
+            history_by_id: BTreeMap::new(),
+            pending_transfers: BTreeSet::new(),
         }
     }
 
@@ -164,7 +524,7 @@ impl NonFungibleToken {
         if self.token_approvals.get(&token_id).unwrap_or(&ZERO_ID) == account {
             return AuthAccount::ApprovedActor;
         }
-        if self.operator_approval.contains_key(owner) {
+        if self.operator_approval.get(owner) == Some(account) {
             return AuthAccount::Operator;
         }
         AuthAccount::None
@@ -173,6 +533,228 @@ impl NonFungibleToken {
     pub fn exists(&self, token_id: U256) -> bool {
         self.owner_by_id.contains_key(&token_id)
     }
+
+    /// Transfers an NFT item to the program `to` and notifies it with `NftReceiverAction::OnNftReceived`
+    /// The ownership is finalized only if `to` replies with `NftReceiverReply::Accepted`
+    /// or passes the token on before replying, otherwise the transfer is reverted and `Event::TransferRejected` is sent back
+    /// Until `to` replies, the token and the tokens nested into it can't be nested, detached, locked, approved or rented out
+    /// A user account never replies, so the transfer to it is done like `transfer` without the notification
+    /// Arguments:
+    /// * `from`: the valid ActorId. It can the the token owner or the actor with the right to transfer the token
+    /// * `to`: the program or the account to which the token will be sent
+    /// * `token_id`: the ID of the token to transfer
+    /// * `data`: additional data passed to `to` with no specified format
+    /// * `to_program`: true if `to` is a program, the runtime can't tell programs from accounts,
+    ///   so the caller must state it
+    pub async fn safe_transfer(
+        &mut self,
+        from: &ActorId,
+        to: &ActorId,
+        token_id: U256,
+        data: Vec<u8>,
+        to_program: bool,
+    ) {
+        if !to_program {
+            self.transfer(from, to, token_id);
+            return;
+        }
+        let owner = self.check_transfer(from, to, token_id);
+        self.check_not_pending(token_id);
+        let approval = self.token_approvals.get(&token_id).copied();
+        // the ownership histories are restored if the token comes back, so the rejected transfer isn't recorded
+        let mut token_ids = self.descendants(token_id);
+        token_ids.push(token_id);
+        let histories: Vec<(U256, Option<Vec<OwnershipRecord>>)> = token_ids
+            .iter()
+            .map(|token_id| (*token_id, self.history_by_id.get(token_id).cloned()))
+            .collect();
+        // `to` owns the tokens while its reply is awaited, but it can't nest, detach, lock, approve or rent them out,
+        // so they can be given back as they were if it rejects them
+        self.pending_transfers.extend(token_ids.iter().copied());
+        self.move_token(&owner, to, token_id);
+
+        let reply = msg::send_and_wait_for_reply(
+            *to,
+            NftReceiverAction::OnNftReceived {
+                operator: *from,
+                from: owner,
+                token_id,
+                data,
+            },
+            GAS_FOR_RECEIVER,
+            0,
+        )
+        .await;
+        for token_id in &token_ids {
+            self.pending_transfers.remove(token_id);
+        }
+
+        // `to` could have passed the token on while waiting for the reply, then the transfer can't be reverted
+        let accepted = matches!(reply, Ok(NftReceiverReply::Accepted));
+        if accepted || !self.is_token_owner(token_id, to) {
+            msg::reply(
+                Event::Transfer {
+                    from: *from,
+                    to: *to,
+                    token_id,
+                },
+                exec::gas_available() - GAS_RESERVE,
+                0,
+            );
+            return;
+        }
+
+        self.move_token(to, &owner, token_id);
+        if let Some(approved) = approval {
+            self.token_approvals.insert(token_id, approved);
+        }
+        for (token_id, history) in histories {
+            match history {
+                Some(history) => self.history_by_id.insert(token_id, history),
+                None => self.history_by_id.remove(&token_id),
+            };
+        }
+        msg::reply(
+            Event::TransferRejected {
+                from: *from,
+                to: *to,
+                token_id,
+            },
+            exec::gas_available() - GAS_RESERVE,
+            0,
+        );
+    }
+
+    /// Panics if the token or any token nested into it is soulbound
+    pub fn check_not_soulbound(&self, token_id: U256) {
+        if self.is_soulbound(token_id) {
+            panic!("NonFungibleToken: token is soulbound");
+        }
+        if self
+            .descendants(token_id)
+            .iter()
+            .any(|child| self.is_soulbound(*child))
+        {
+            panic!("NonFungibleToken: nested token is soulbound");
+        }
+    }
+
+    /// Panics if the token is locked
+    pub fn check_unlocked(&self, token_id: U256) {
+        if self.locked_by.contains_key(&token_id) {
+            panic!("NonFungibleToken: token is locked");
+        }
+    }
+
+    /// Panics if the token is being transferred with `safe_transfer` and waits for the receiver's reply
+    pub fn check_not_pending(&self, token_id: U256) {
+        if self.pending_transfers.contains(&token_id) {
+            panic!("NonFungibleToken: token transfer is pending");
+        }
+    }
+
+    /// Panics if the token or any token nested into it is locked
+    pub fn check_tree_unlocked(&self, token_id: U256) {
+        self.check_unlocked(token_id);
+        if self
+            .descendants(token_id)
+            .iter()
+            .any(|child| self.locked_by.contains_key(child))
+        {
+            panic!("NonFungibleToken: nested token is locked");
+        }
+    }
+
+    fn check_metadata_mutable(&self, token_id: U256) {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        if self.is_metadata_frozen(token_id) {
+            panic!("NonFungibleToken: metadata is frozen");
+        }
+    }
+
+    /// Checks that `from` is able to transfer the token to `to` and returns the token owner
+    /// `from` can transfer the token to itself if it's an approved actor or an operator
+    pub fn check_transfer(&self, from: &ActorId, to: &ActorId, token_id: U256) -> ActorId {
+        if !self.exists(token_id) {
+            panic!("NonFungibleToken: token does not exist");
+        }
+        if self.is_token_owner(token_id, to) {
+            panic!("NonFungibleToken: Transfer to current owner");
+        }
+        if to == &ZERO_ID {
+            panic!("NonFungibleToken: Transfer to zero address.");
+        }
+        if let AuthAccount::None = self.authorized_actor(token_id, from) {
+            panic!("NonFungibleToken: is not an authorized source");
+        }
+        self.check_not_soulbound(token_id);
+        self.check_tree_unlocked(token_id);
+        if self.parent_by_id.contains_key(&token_id) {
+            panic!("NonFungibleToken: token is nested");
+        }
+        *self.owner_by_id.get(&token_id).unwrap_or(&ZERO_ID)
+    }
+
+    /// Moves the token and the tokens nested into it from `owner` to `to`
+    /// updating balances and history and clearing the token approvals and users
+    pub fn move_token(&mut self, owner: &ActorId, to: &ActorId, token_id: U256) {
+        self.move_tree(owner, to, token_id, None);
+    }
+
+    /// Moves the token like `move_token` and records `price` as the price the token was sold for
+    pub fn sell_token(&mut self, owner: &ActorId, to: &ActorId, token_id: U256, price: u128) {
+        self.move_tree(owner, to, token_id, Some(price));
+    }
+
+    fn move_tree(&mut self, owner: &ActorId, to: &ActorId, root: U256, price: Option<u128>) {
+        let mut token_ids = self.descendants(root);
+        token_ids.push(root);
+        for token_id in token_ids {
+            self.token_approvals.remove(&token_id);
+            self.user_by_id.remove(&token_id);
+            // the nested tokens are moved together with the sold one, but they aren't sold themselves
+            let price = if token_id == root { price } else { None };
+            self.record_ownership(token_id, owner, to, price);
+
+            let owner_balance = *self.balances.get(owner).unwrap_or(&U256::zero());
+            let to_balance = *self.balances.get(to).unwrap_or(&U256::zero());
+
+            self.balances
+                .insert(*owner, owner_balance.saturating_sub(U256::one()));
+            self.balances
+                .insert(*to, to_balance.saturating_add(U256::one()));
+
+            self.owner_by_id.insert(token_id, *to);
+        }
+    }
+
+    /// Returns the tokens of this program nested into the token directly or through other tokens
+    pub fn descendants(&self, token_id: U256) -> Vec<U256> {
+        let mut descendants = Vec::new();
+        let mut pending = self.children_of(token_id);
+        while let Some(child) = pending.pop() {
+            pending.extend(self.children_of(child));
+            descendants.push(child);
+        }
+        descendants
+    }
+
+    /// Removes the link between the token and its parent, the token keeps its owner
+    pub fn unlink(&mut self, token_id: U256) {
+        if let Some((program, parent_id)) = self.parent_by_id.remove(&token_id) {
+            if program != exec::program_id() {
+                return;
+            }
+            if let Some(children) = self.children_by_id.get_mut(&parent_id) {
+                children.remove(&token_id);
+                if children.is_empty() {
+                    self.children_by_id.remove(&parent_id);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Encode, TypeInfo, Decode)]
@@ -194,6 +776,11 @@ pub enum Event {
     },
     OwnerOf(ActorId),
     BalanceOf(U256),
+    TransferRejected {
+        from: ActorId,
+        to: ActorId,
+        token_id: U256,
+    },
 }
 
 #[derive(Debug, Encode, TypeInfo)]
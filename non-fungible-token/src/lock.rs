@@ -0,0 +1,25 @@
+use gstd::ActorId;
+use primitive_types::U256;
+
+pub trait NonFungibleTokenLockable {
+    /// Locks the token without moving it, so staking, lending or rental programs can keep it in place
+    /// While the token is locked, `transfer`, `approve`, `nest` and `detach` must panic for it,
+    /// `transfer`, `nest` and `detach` must panic for the tokens it is nested into as well
+    /// Arguments:
+    /// * `caller`: the valid ActorId that must be the token owner or the actor with the right to manage the token
+    /// * `token_id`: the token ID
+    /// * `locker`: the actor that is the only one allowed to unlock the token
+    /// Contract must panic if the token does not exist, is already locked, `caller` is not authorized,
+    /// `locker` is the zero address or the token waits for the receiver's reply to `safe_transfer`
+    fn lock(&mut self, caller: &ActorId, token_id: U256, locker: &ActorId);
+
+    /// Unlocks the token
+    /// Arguments:
+    /// * `caller`: the valid ActorId that must be the locker of the token
+    /// * `token_id`: the token ID
+    /// Contract must panic if the token is not locked or `caller` is not its locker
+    fn unlock(&mut self, caller: &ActorId, token_id: U256);
+
+    /// Returns the actor that locked the token, or None if the token is not locked
+    fn locker_of(&self, token_id: U256) -> Option<ActorId>;
+}
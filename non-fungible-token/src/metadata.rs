@@ -0,0 +1,34 @@
+use crate::token::{AttributeValue, TokenMetadata};
+use gstd::prelude::*;
+use primitive_types::U256;
+
+pub trait NonFungibleTokenMetadata {
+    /// Replaces the whole metadata of the token
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `metadata`: the new metadata of the token
+    /// Contract must panic if the token does not exist or its metadata is frozen
+    fn set_metadata(&mut self, token_id: U256, metadata: TokenMetadata);
+
+    /// Adds the attribute to the token metadata or replaces the value of the existing one
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `key`: the name of the attribute
+    /// * `value`: the new value of the attribute
+    /// Contract must panic if the token does not exist or its metadata is frozen
+    fn set_attribute(&mut self, token_id: U256, key: String, value: AttributeValue);
+
+    /// Removes the attribute from the token metadata
+    /// Contract must panic if the token does not exist or its metadata is frozen
+    fn remove_attribute(&mut self, token_id: U256, key: String);
+
+    /// Makes the metadata of the token immutable. It can not be undone
+    /// Contract must panic if the token does not exist
+    fn freeze_metadata(&mut self, token_id: U256);
+
+    /// Makes the metadata of all tokens immutable. It can not be undone
+    fn freeze_all_metadata(&mut self);
+
+    /// Returns true if the metadata of the token can no longer be changed
+    fn is_metadata_frozen(&self, token_id: U256) -> bool;
+}
@@ -0,0 +1,42 @@
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+/// The number of the latest ownership changes kept for each token
+pub const MAX_HISTORY_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, Decode, Encode, TypeInfo)]
+pub struct OwnershipRecord {
+    /// The previous owner, the zero address for a mint
+    pub from: ActorId,
+    /// The new owner, the zero address for a burn
+    pub to: ActorId,
+    pub block_height: u32,
+    /// The price the token was sold for, if the change was a sale
+    pub price: Option<u128>,
+}
+
+pub trait NonFungibleTokenProvenance {
+    /// Records the change of the token owner
+    /// Only the latest `MAX_HISTORY_LEN` changes are kept, the oldest one is dropped first
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `from`: the previous owner
+    /// * `to`: the new owner
+    /// * `price`: the price the token was sold for
+    fn record_ownership(
+        &mut self,
+        token_id: U256,
+        from: &ActorId,
+        to: &ActorId,
+        price: Option<u128>,
+    );
+
+    /// Returns the ownership changes of the token from the oldest to the latest
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `offset`: the number of changes to skip
+    /// * `limit`: the maximum number of changes to return
+    fn token_history(&self, token_id: U256, offset: u32, limit: u32) -> Vec<OwnershipRecord>;
+}
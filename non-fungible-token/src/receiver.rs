@@ -0,0 +1,28 @@
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+/// The message a program receives when an NFT is sent to it with `safe_transfer`
+#[derive(Debug, Decode, Encode, TypeInfo)]
+pub enum NftReceiverAction {
+    /// Arguments:
+    /// * `operator`: the actor who initiated the transfer
+    /// * `from`: the previous owner of the token
+    /// * `token_id`: the ID of the received token
+    /// * `data`: additional data with no specified format
+    OnNftReceived {
+        operator: ActorId,
+        from: ActorId,
+        token_id: U256,
+        data: Vec<u8>,
+    },
+}
+
+/// The reply a receiving program must send back to `OnNftReceived`
+/// The transfer is finalized only if the receiver replies with `Accepted`
+#[derive(Debug, Decode, Encode, TypeInfo)]
+pub enum NftReceiverReply {
+    Accepted,
+    Rejected,
+}
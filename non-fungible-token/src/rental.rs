@@ -0,0 +1,32 @@
+use codec::{Decode, Encode};
+use gstd::ActorId;
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+#[derive(Debug, Clone, Copy, Decode, Encode, TypeInfo)]
+pub struct UserInfo {
+    /// The account that is allowed to use the token
+    pub user: ActorId,
+    /// The timestamp in milliseconds when the user role expires
+    pub expires: u64,
+}
+
+pub trait NonFungibleTokenRental {
+    /// Gives the user role of the token to the actor until `expires`
+    /// The user role is cleared when the token is transferred
+    /// Arguments:
+    /// * `caller`: the valid ActorId that must be the token owner or the actor with the right to manage the token
+    /// * `token_id`: the token ID
+    /// * `user`: the new user of the token, the zero ID removes the current user
+    /// * `expires`: the block timestamp in milliseconds when the user role expires
+    /// Contract must panic if the token does not exist, `caller` is not authorized to manage it
+    /// or the token waits for the receiver's reply to `safe_transfer`
+    fn set_user(&mut self, caller: &ActorId, token_id: U256, user: &ActorId, expires: u64);
+
+    /// Returns the current user of the token
+    /// If there is no user or the user role has expired, it returns the zero address
+    fn user_of(&self, token_id: U256) -> ActorId;
+
+    /// Returns the timestamp when the user role of the token expires, or zero if there is no user
+    fn user_expires(&self, token_id: U256) -> u64;
+}
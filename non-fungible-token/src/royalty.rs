@@ -0,0 +1,68 @@
+use codec::{Decode, Encode};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
+use scale_info::TypeInfo;
+
+/// The denominator of royalty rates: 10_000 basis points is 100%
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+#[derive(Debug, Clone, Copy, Decode, Encode, TypeInfo)]
+pub struct RoyaltyInfo {
+    /// The account that receives the royalty
+    pub receiver: ActorId,
+    /// The royalty rate in basis points
+    pub rate: u16,
+}
+
+pub trait NonFungibleTokenRoyalty {
+    /// Sets the royalty applied to all tokens that have no royalty of their own
+    /// Arguments:
+    /// * `receiver`: the valid ActorId that receives the royalty
+    /// * `rate`: the royalty rate in basis points
+    /// Contract must panic if `rate` exceeds `MAX_BASIS_POINTS`
+    fn set_default_royalty(&mut self, receiver: &ActorId, rate: u16);
+
+    /// Removes the collection default royalty
+    fn delete_default_royalty(&mut self);
+
+    /// Sets the royalty of the specific token overriding the collection default
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `receiver`: the valid ActorId that receives the royalty
+    /// * `rate`: the royalty rate in basis points
+    /// Contract must panic if `rate` exceeds `MAX_BASIS_POINTS`
+    fn set_token_royalty(&mut self, token_id: U256, receiver: &ActorId, rate: u16);
+
+    /// Splits the royalty of the specific token among several receivers overriding the collection default
+    /// The royalty rate of the token becomes the sum of the shares
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `shares`: the receivers with their royalty rates in basis points
+    /// Contract must panic if `shares` is empty, contains the zero address or the rates sum exceeds `MAX_BASIS_POINTS`
+    fn set_royalty_shares(&mut self, token_id: U256, shares: Vec<(ActorId, u16)>);
+
+    /// Removes the royalty of the specific token, so the collection default applies to it again
+    fn reset_token_royalty(&mut self, token_id: U256);
+
+    /// Returns the royalty receiver and the royalty amount for the sale of the token
+    /// If neither the token nor the collection has a royalty, it returns the zero address and zero amount
+    /// If the royalty of the token is split, it returns the first receiver with the whole amount,
+    /// so the royalty must be paid according to `royalty_breakdown`
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `sale_price`: the price the token is sold for
+    fn royalty_info(&self, token_id: U256, sale_price: u128) -> (ActorId, u128);
+
+    /// Returns each royalty receiver with its amount for the sale of the token
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `sale_price`: the price the token is sold for
+    fn royalty_breakdown(&self, token_id: U256, sale_price: u128) -> Vec<(ActorId, u128)>;
+}
+
+/// Calculates `rate` basis points of `price` without overflowing
+pub fn royalty_amount(price: u128, rate: u16) -> u128 {
+    let denominator = MAX_BASIS_POINTS as u128;
+    let rate = rate as u128;
+    price / denominator * rate + price % denominator * rate / denominator
+}
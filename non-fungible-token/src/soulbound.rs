@@ -0,0 +1,20 @@
+use primitive_types::U256;
+
+pub trait NonFungibleTokenSoulbound {
+    /// Makes all tokens of the collection non-transferable or transferable again
+    /// While the collection is soulbound, `transfer`, `approve` and `approve_for_all` must panic
+    /// Arguments:
+    /// * `soulbound`: True to bind the tokens to their owners, false to unbind them
+    fn set_soulbound(&mut self, soulbound: bool);
+
+    /// Makes the specific token non-transferable or transferable again
+    /// While the token is soulbound, `transfer` and `approve` must panic for it
+    /// Arguments:
+    /// * `token_id`: the token ID
+    /// * `soulbound`: True to bind the token to its owner, false to unbind it
+    /// Contract must panic if the token does not exist
+    fn set_token_soulbound(&mut self, token_id: U256, soulbound: bool);
+
+    /// Returns true if the token can not be transferred either by itself or by the collection flag
+    fn is_soulbound(&self, token_id: U256) -> bool;
+}
@@ -2,7 +2,7 @@ use codec::{Decode, Encode};
 use gstd::prelude::*;
 use scale_info::TypeInfo;
 
-#[derive(Debug, Decode, Encode, TypeInfo)]
+#[derive(Debug, Default, Clone, Decode, Encode, TypeInfo)]
 pub struct TokenMetadata {
     /// The title of NFT Item: for example "CryptoKitty #2505"
     pub title: Option<String>,
@@ -12,4 +12,13 @@ pub struct TokenMetadata {
     pub media: Option<String>,
     /// URL to an off-chain JSON file with more info
     pub reference: Option<String>,
+    /// On-chain attributes of the item: for example ("strength", Number(10))
+    pub attributes: Vec<(String, AttributeValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Decode, Encode, TypeInfo)]
+pub enum AttributeValue {
+    Text(String),
+    Number(i64),
+    Boolean(bool),
 }